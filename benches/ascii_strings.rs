@@ -0,0 +1,39 @@
+//! Compares tokenizing a pure ASCII document, which takes the fast path for strings, against
+//! the same document with a single non-ASCII character in it.
+//!
+//! Run with `cargo bench --bench ascii_strings`.
+
+use hdjson::tokenizer::Tokenizer;
+use std::hint::black_box;
+use std::time::Instant;
+
+const RECORDS: usize = 20_000;
+const ROUNDS: usize = 10;
+
+fn main() {
+    let records: Vec<String> = (0..RECORDS)
+        .map(|i| {
+            format!(
+                r#"{{"id":"record-{}","name":"A fairly ordinary name","text":"Some longer text that is typical for descriptions and comments in documents"}}"#,
+                i
+            )
+        })
+        .collect();
+    let ascii = format!("[{}]", records.join(","));
+    // Only differs in a single string, but disables the fast path for the whole document.
+    let non_ascii = ascii.replacen("record-0", "récord-0", 1);
+
+    let tokenize = |document: &str| {
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            black_box(Tokenizer::new(document.chars()).tokenize());
+        }
+        start.elapsed() / ROUNDS as u32
+    };
+    let ascii_time = tokenize(&ascii);
+    let non_ascii_time = tokenize(&non_ascii);
+
+    println!("{} records, {} bytes", RECORDS, ascii.len());
+    println!("ascii:     {:?}/tokenize", ascii_time);
+    println!("non-ascii: {:?}/tokenize", non_ascii_time);
+}
@@ -0,0 +1,58 @@
+//! Compares parsing a number-heavy document with and without `ParseOptions::lazy_numbers`.
+//!
+//! Run with `cargo bench --bench lazy_numbers`.
+
+use hdjson::tokenizer::Tokenizer;
+use hdjson::{JsonValue, ParseOptions, Parser};
+use std::hint::black_box;
+use std::time::Instant;
+
+const NUMBERS: usize = 100_000;
+const ROUNDS: usize = 10;
+
+fn main() {
+    let numbers: Vec<String> = (0..NUMBERS)
+        .map(|i| (i as i64 * 7_919 - 400_000_000).to_string())
+        .collect();
+    let document = format!("[{}]", numbers.join(","));
+
+    let parse = |lazy_numbers: bool| {
+        let options = ParseOptions {
+            lazy_numbers,
+            ..ParseOptions::default()
+        };
+        Parser::with_options(Tokenizer::new(document.chars()), options)
+            .parse()
+            .unwrap()
+    };
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(parse(false));
+    }
+    let eager_time = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(parse(true));
+    }
+    let lazy_time = start.elapsed();
+
+    // Reading every number afterwards, the worst case for lazy parsing.
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let JsonValue::Array(elements) = parse(true) else {
+            unreachable!();
+        };
+        for element in &elements {
+            black_box(element.as_i64_coerced());
+        }
+    }
+    let lazy_read_time = start.elapsed();
+
+    let rounds = ROUNDS as u32;
+    println!("{} numbers, {} bytes", NUMBERS, document.len());
+    println!("eager:             {:?}/parse", eager_time / rounds);
+    println!("lazy:              {:?}/parse", lazy_time / rounds);
+    println!("lazy, all read:    {:?}/parse", lazy_read_time / rounds);
+}
@@ -0,0 +1,49 @@
+//! Compares key lookups in an object against a `HashMap`.
+//!
+//! Run with `cargo bench --bench object_lookup`, and with `--features indexmap` to measure the
+//! `IndexMap` backed `Object`.
+
+use hdjson::{JsonValue, Object};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::Instant;
+
+const KEYS: usize = 1000;
+const ROUNDS: usize = 100;
+
+fn main() {
+    let object = JsonValue::Object(
+        (0..KEYS)
+            .map(|i| (format!("key{}", i), JsonValue::Number((i as i64).into())))
+            .collect::<Object>(),
+    );
+    let map: HashMap<String, JsonValue> = (0..KEYS)
+        .map(|i| (format!("key{}", i), JsonValue::Number((i as i64).into())))
+        .collect();
+    let keys: Vec<String> = (0..KEYS).map(|i| format!("key{}", i)).collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for key in &keys {
+            black_box(object.get(key));
+        }
+    }
+    let object_time = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for key in &keys {
+            black_box(map.get(key));
+        }
+    }
+    let map_time = start.elapsed();
+
+    let lookups = (KEYS * ROUNDS) as u32;
+    println!("{} lookups on a {}-key object", lookups, KEYS);
+    println!(
+        "object:  {:?} ({:?}/lookup)",
+        object_time,
+        object_time / lookups
+    );
+    println!("hashmap: {:?} ({:?}/lookup)", map_time, map_time / lookups);
+}
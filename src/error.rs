@@ -0,0 +1,295 @@
+//! Errors reported while parsing JSON inputs.
+
+use crate::json_value::JsonType;
+use crate::parser::NumberType;
+use crate::tokenizer::TokenType;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    UnexpectedChar {
+        ch: char,
+        line: i32,
+        col: i32,
+    },
+    // A `//` or `/*` comment outside of JSONC mode.
+    CommentNotAllowed {
+        line: i32,
+        col: i32,
+    },
+    UnexpectedToken {
+        found: TokenType<'static>,
+        line: i32,
+        col: i32,
+    },
+    UnexpectedEof {
+        line: i32,
+        col: i32,
+    },
+    InvalidNumber {
+        line: i32,
+        col: i32,
+    },
+    // The input ended inside a string, the position is that of its opening quote.
+    UnterminatedString {
+        line: i32,
+        col: i32,
+    },
+    // `found` is the escape sequence as written, up to the character that made it invalid.
+    InvalidEscape {
+        found: String,
+        line: i32,
+        col: i32,
+    },
+    // U+0000 to U+001F must be escaped inside strings.
+    ControlCharInString {
+        ch: char,
+        line: i32,
+        col: i32,
+    },
+    // The tokenizer produced more than `TokenizerOptions::max_tokens` tokens.
+    TooManyTokens {
+        limit: usize,
+        line: i32,
+        col: i32,
+    },
+    // A number did not match the type the schema expects at its path.
+    NumberTypeMismatch {
+        expected: NumberType,
+        line: i32,
+        col: i32,
+    },
+    // An object contained `key` twice, see `DuplicateKeys::Reject`.
+    DuplicateKey {
+        key: String,
+        line: i32,
+        col: i32,
+    },
+    // A number had more digits than `TokenizerOptions::max_number_digits` allows.
+    NumberTooLong {
+        limit: usize,
+        line: i32,
+        col: i32,
+    },
+    // Arrays and objects were nested deeper than `ParseOptions::max_depth` allows.
+    TooDeep {
+        limit: usize,
+        line: i32,
+        col: i32,
+    },
+    // The input held no tokens at all, see `TokenizerOptions::reject_empty`.
+    EmptyDocument,
+    // A length-prefixed frame announced more bytes than were available.
+    TruncatedFrame {
+        expected: usize,
+        available: usize,
+    },
+    // The input was not valid UTF-8, `offset` is the byte offset of the first invalid byte.
+    InvalidUtf8 {
+        offset: usize,
+    },
+    // Nothing exists at the JSON Pointer `pointer`.
+    PointerNotFound {
+        pointer: String,
+    },
+    // The value at `pointer` could not be converted from one type to another.
+    InvalidConversion {
+        pointer: String,
+        from: JsonType,
+        to: JsonType,
+    },
+    // An object had no member named `key`.
+    MissingKey {
+        key: String,
+    },
+    // A key was looked up in a value of type `found` rather than an object.
+    NotAnObject {
+        found: JsonType,
+    },
+    // Wraps an error that occurred inside a nested value, `path` is the JSON Pointer to it.
+    Context {
+        path: String,
+        source: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    /// JSON Pointer to the value in which the error occurred, if it was not at the top level.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            ParseError::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, with any context stripped.
+    pub fn root_cause(&self) -> &ParseError {
+        match self {
+            ParseError::Context { source, .. } => source.root_cause(),
+            _ => self,
+        }
+    }
+
+    /// Whether the error is confined to a single token or value, so parsing could resume after
+    /// skipping it, e.g. at the next line of a stream. Running out of input, exceeding a limit
+    /// or broken encodings are fatal.
+    pub fn is_recoverable(&self) -> bool {
+        match self.root_cause() {
+            ParseError::UnexpectedChar { .. }
+            | ParseError::CommentNotAllowed { .. }
+            | ParseError::UnexpectedToken { .. }
+            | ParseError::InvalidNumber { .. }
+            | ParseError::InvalidEscape { .. }
+            | ParseError::ControlCharInString { .. }
+            | ParseError::NumberTypeMismatch { .. }
+            | ParseError::NumberTooLong { .. }
+            | ParseError::DuplicateKey { .. } => true,
+            ParseError::UnexpectedEof { .. }
+            | ParseError::UnterminatedString { .. }
+            | ParseError::TooManyTokens { .. }
+            | ParseError::TooDeep { .. }
+            | ParseError::EmptyDocument
+            | ParseError::TruncatedFrame { .. }
+            | ParseError::InvalidUtf8 { .. }
+            | ParseError::PointerNotFound { .. }
+            | ParseError::InvalidConversion { .. }
+            | ParseError::MissingKey { .. }
+            | ParseError::NotAnObject { .. }
+            | ParseError::Context { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar {
+                ch: '\u{feff}',
+                line,
+                col,
+            } => write!(
+                f,
+                "illegal byte order mark (U+FEFF) at line {}, column {}",
+                line, col
+            ),
+            ParseError::UnexpectedChar { ch, line, col } => write!(
+                f,
+                "unexpected character '{}' at line {}, column {}",
+                ch, line, col
+            ),
+            ParseError::CommentNotAllowed { line, col } => write!(
+                f,
+                "comments are not allowed in strict JSON at line {}, column {}",
+                line, col
+            ),
+            ParseError::UnexpectedToken { found, line, col } => {
+                write!(f, "unexpected {} at line {}, column {}", found, line, col)
+            }
+            ParseError::UnexpectedEof { line, col } => {
+                write!(
+                    f,
+                    "unexpected end of input at line {}, column {}",
+                    line, col
+                )
+            }
+            ParseError::InvalidNumber { line, col } => {
+                write!(f, "invalid number at line {}, column {}", line, col)
+            }
+            ParseError::UnterminatedString { line, col } => write!(
+                f,
+                "unterminated string starting at line {}, column {}",
+                line, col
+            ),
+            ParseError::InvalidEscape { found, line, col } => {
+                write!(
+                    f,
+                    "invalid escape sequence {} at line {}, column {}",
+                    found, line, col
+                )
+            }
+            ParseError::ControlCharInString { ch, line, col } => write!(
+                f,
+                "unescaped control character U+{:04X} in string at line {}, column {}",
+                *ch as u32, line, col
+            ),
+            ParseError::TooManyTokens { limit, line, col } => write!(
+                f,
+                "more than {} tokens at line {}, column {}",
+                limit, line, col
+            ),
+            ParseError::NumberTypeMismatch {
+                expected,
+                line,
+                col,
+            } => {
+                let expected = match expected {
+                    NumberType::I64 => "an integer",
+                    NumberType::F64 => "a number",
+                };
+                write!(f, "expected {} at line {}, column {}", expected, line, col)
+            }
+            ParseError::NumberTooLong { limit, line, col } => write!(
+                f,
+                "number with more than {} digits at line {}, column {}",
+                limit, line, col
+            ),
+            ParseError::TooDeep { limit, line, col } => write!(
+                f,
+                "more than {} nested arrays and objects at line {}, column {}",
+                limit, line, col
+            ),
+            ParseError::EmptyDocument => write!(f, "empty document"),
+            ParseError::TruncatedFrame {
+                expected,
+                available,
+            } => write!(
+                f,
+                "truncated frame, expected {} bytes but only {} are available",
+                expected, available
+            ),
+            ParseError::InvalidUtf8 { offset } => {
+                write!(f, "invalid UTF-8 at byte offset {}", offset)
+            }
+            ParseError::PointerNotFound { pointer } => write!(f, "no value at {}", pointer),
+            ParseError::InvalidConversion { pointer, from, to } => {
+                write!(f, "cannot convert {} at {} to {}", from, pointer, to)
+            }
+            ParseError::DuplicateKey { key, line, col } => write!(
+                f,
+                "duplicate key \"{}\" at line {}, column {}",
+                key, line, col
+            ),
+            ParseError::MissingKey { key } => write!(f, "missing key \"{}\"", key),
+            ParseError::NotAnObject { found } => write!(f, "expected an object, found {}", found),
+            ParseError::Context { path, source } => write!(f, "{} in {}", source, path),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ParseError;
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn is_recoverable() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert!(parse("[1,,2]").unwrap_err().is_recoverable());
+        assert!(parse(r#"{"a":tru}"#).unwrap_err().is_recoverable());
+        assert!(parse("[1 2]").unwrap_err().is_recoverable());
+        assert!(!parse(r#"{"a":[1,"#).unwrap_err().is_recoverable());
+        assert!(!ParseError::EmptyDocument.is_recoverable());
+        assert!(!ParseError::InvalidUtf8 { offset: 3 }.is_recoverable());
+    }
+}
@@ -0,0 +1,445 @@
+//! Accessors for reading the contents of JSON values.
+
+use crate::error::ParseError;
+use crate::json_value::{member, JsonType, JsonValue, Number, Object};
+use crate::parser::escape_pointer_token;
+use std::ops::Index;
+
+/// Looks up a key like `JsonValue::get`, panicking if the value is not an object or has no such
+/// key.
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        match self.get(key) {
+            Some(member) => member,
+            None if self.is_object() => panic!("no key \"{}\" in object", key),
+            None => panic!("cannot index {} with key \"{}\"", self.json_type(), key),
+        }
+    }
+}
+
+/// Returns an array element like `JsonValue::get_index`, panicking if the value is not an array
+/// or the index is out of range.
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        match self {
+            JsonValue::Array(elements) => &elements[index],
+            _ => panic!("cannot index {} with {}", self.json_type(), index),
+        }
+    }
+}
+
+impl JsonValue {
+    pub fn json_type(&self) -> JsonType {
+        match self {
+            JsonValue::Null => JsonType::Null,
+            JsonValue::Boolean(_) => JsonType::Boolean,
+            JsonValue::Number(_) => JsonType::Number,
+            JsonValue::String(_) => JsonType::String,
+            JsonValue::Array(_) => JsonType::Array,
+            JsonValue::Object(_) => JsonType::Object,
+        }
+    }
+
+    /// Null, booleans, numbers and strings.
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+
+    /// Arrays and objects.
+    pub fn is_container(&self) -> bool {
+        matches!(self, JsonValue::Array(_) | JsonValue::Object(_))
+    }
+
+    /// Returns `Some(())` for null and `None` for everything else.
+    pub fn as_null(&self) -> Option<()> {
+        match self {
+            JsonValue::Null => Some(()),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            JsonValue::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// The number as an i64, see `Number::as_i64`. Strings holding numbers give `None`, unlike
+    /// with `as_i64_coerced`.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    /// The number as an f64, see `Number::as_f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number()?.as_f64()
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Object> {
+        match self {
+            JsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, JsonValue::Boolean(_))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, JsonValue::Number(_))
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonValue::String(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonValue::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonValue::Object(_))
+    }
+
+    /// Reads a nullable value: null gives `Some(None)`, anything else is passed to `f`, so
+    /// `Some(Some(_))` is a present value and `None` one of the wrong kind.
+    pub fn null_or<T>(&self, f: impl FnOnce(&JsonValue) -> Option<T>) -> Option<Option<T>> {
+        match self {
+            JsonValue::Null => Some(None),
+            _ => f(self).map(Some),
+        }
+    }
+
+    /// Returns `default` if the value is null and the value itself otherwise.
+    pub fn coalesce<'a>(&'a self, default: &'a JsonValue) -> &'a JsonValue {
+        match self {
+            JsonValue::Null => default,
+            _ => self,
+        }
+    }
+
+    /// Looks up `key` in an object by scanning its members, or by hash with the `indexmap`
+    /// feature. Returns `None` if the value is not an object or the key is missing. With
+    /// duplicate keys the first occurrence is used.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        let JsonValue::Object(members) = self else {
+            return None;
+        };
+        member(members, key)
+    }
+
+    /// Returns the element at `index` of an array, or `None` if the value is not an array or
+    /// the index is out of range.
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up `key` in an object, falling back to `default` if the value is not an object, the
+    /// key is missing, or the member is null. With duplicate keys the first occurrence is used.
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a JsonValue) -> &'a JsonValue {
+        self.get(key)
+            .map_or(default, |member| member.coalesce(default))
+    }
+
+    /// Looks up `key` in an object like `get`, but fails with `ParseError::MissingKey` or
+    /// `ParseError::NotAnObject` instead of returning `None`, so lookups can be chained with `?`.
+    pub fn try_get(&self, key: &str) -> Result<&JsonValue, ParseError> {
+        let JsonValue::Object(members) = self else {
+            return Err(ParseError::NotAnObject {
+                found: self.json_type(),
+            });
+        };
+        member(members, key).ok_or_else(|| ParseError::MissingKey {
+            key: key.to_string(),
+        })
+    }
+
+    /// Looks up a member of nested objects, one key per level. Errors below the top level carry
+    /// the JSON Pointer of the object the lookup failed in, see `ParseError::path`.
+    pub fn try_get_path(&self, keys: &[&str]) -> Result<&JsonValue, ParseError> {
+        let mut value = self;
+        let mut path = String::new();
+        for key in keys {
+            value = value.try_get(key).map_err(|error| {
+                if path.is_empty() {
+                    return error;
+                }
+                ParseError::Context {
+                    path: path.clone(),
+                    source: Box::new(error),
+                }
+            })?;
+            path.push('/');
+            path.push_str(&escape_pointer_token(key));
+        }
+        Ok(value)
+    }
+
+    /// Lenient integer access: returns numbers as-is and also parses strings such as `"42"`,
+    /// for inputs that send numbers as text. Any other value, or a string that is not an integer,
+    /// gives `None`.
+    pub fn as_i64_coerced(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(number) => number.as_i64(),
+            JsonValue::String(string) => string.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Lenient float access, see `as_i64_coerced`.
+    pub fn as_f64_coerced(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(number) => number.as_f64(),
+            JsonValue::String(string) => string.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ParseError;
+    use crate::json_value::{JsonValue, Object};
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn scalars_and_containers() {
+        let scalars = [
+            JsonValue::Null,
+            JsonValue::Boolean(false),
+            JsonValue::Number(1.into()),
+            JsonValue::String(String::new()),
+        ];
+        for value in &scalars {
+            assert!(value.is_scalar());
+            assert!(!value.is_container());
+        }
+
+        let containers = [JsonValue::Array(vec![]), JsonValue::Object(Object::new())];
+        for value in &containers {
+            assert!(!value.is_scalar());
+            assert!(value.is_container());
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let default = JsonValue::Number(8080.into());
+        assert_eq!(JsonValue::Null.coalesce(&default), &default);
+        assert_eq!(
+            JsonValue::Number(443.into()).coalesce(&default),
+            &JsonValue::Number(443.into())
+        );
+        assert_eq!(
+            JsonValue::Boolean(false).coalesce(&default),
+            &JsonValue::Boolean(false)
+        );
+
+        let config = JsonValue::Object(Object::from_iter([
+            ("port".to_string(), JsonValue::Number(443.into())),
+            ("timeout".to_string(), JsonValue::Null),
+        ]));
+        assert_eq!(
+            config.get_or("port", &default),
+            &JsonValue::Number(443.into())
+        );
+        assert_eq!(config.get_or("timeout", &default), &default);
+        assert_eq!(config.get_or("host", &default), &default);
+        assert_eq!(JsonValue::Array(vec![]).get_or("port", &default), &default);
+    }
+
+    #[test]
+    fn coerced_numbers() {
+        assert_eq!(
+            JsonValue::String("42".to_string()).as_i64_coerced(),
+            Some(42)
+        );
+        assert_eq!(JsonValue::Number(42.into()).as_i64_coerced(), Some(42));
+        assert_eq!(JsonValue::String("x".to_string()).as_i64_coerced(), None);
+        assert_eq!(JsonValue::String("4.5".to_string()).as_i64_coerced(), None);
+        assert_eq!(JsonValue::Boolean(true).as_i64_coerced(), None);
+
+        assert_eq!(
+            JsonValue::String("4.5".to_string()).as_f64_coerced(),
+            Some(4.5)
+        );
+        assert_eq!(JsonValue::Number(3.into()).as_f64_coerced(), Some(3.0));
+        assert_eq!(JsonValue::String("x".to_string()).as_f64_coerced(), None);
+        assert_eq!(JsonValue::Null.as_f64_coerced(), None);
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let values = [
+            JsonValue::Null,
+            JsonValue::Boolean(true),
+            JsonValue::Number(7.into()),
+            JsonValue::String("seven".to_string()),
+            JsonValue::Array(vec![JsonValue::Null]),
+            JsonValue::Object(Object::from_iter([("a".to_string(), JsonValue::Null)])),
+        ];
+        let predicates = [
+            JsonValue::is_null,
+            JsonValue::is_boolean,
+            JsonValue::is_number,
+            JsonValue::is_string,
+            JsonValue::is_array,
+            JsonValue::is_object,
+        ];
+        for (i, value) in values.iter().enumerate() {
+            for (j, predicate) in predicates.iter().enumerate() {
+                assert_eq!(predicate(value), i == j, "{} {}", value, j);
+            }
+        }
+
+        let [null, boolean, number, string, array, object] = &values;
+        assert_eq!(boolean.as_bool(), Some(true));
+        assert_eq!(number.as_number(), Some(&7.into()));
+        assert_eq!(number.as_i64(), Some(7));
+        assert_eq!(number.as_f64(), Some(7.0));
+        assert_eq!(string.as_str(), Some("seven"));
+        assert_eq!(array.as_array(), Some(&[JsonValue::Null][..]));
+        assert_eq!(
+            object.as_object(),
+            Some(&Object::from_iter([("a".to_string(), JsonValue::Null)]))
+        );
+
+        for value in [null, string, array] {
+            assert_eq!(value.as_bool(), None);
+            assert_eq!(value.as_number(), None);
+            assert_eq!(value.as_i64(), None);
+            assert_eq!(value.as_f64(), None);
+            assert_eq!(value.as_object(), None);
+        }
+        assert_eq!(JsonValue::String("7".to_string()).as_i64(), None);
+        assert_eq!(number.as_str(), None);
+        assert_eq!(object.as_array(), None);
+    }
+
+    #[test]
+    fn index() {
+        let value = Parser::new(Tokenizer::new(r#"{"a":[1,{"b":true}]}"#.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(value["a"][1]["b"], JsonValue::Boolean(true));
+        assert_eq!(
+            value.get("a").and_then(|a| a.get_index(0)),
+            Some(&JsonValue::Number(1.into()))
+        );
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value["a"].get("b"), None);
+        assert_eq!(value["a"].get_index(2), None);
+        assert_eq!(value.get_index(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "no key \"missing\" in object")]
+    fn index_missing_key() {
+        let _ = &JsonValue::Object(Object::new())["missing"];
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn index_out_of_range() {
+        let _ = &JsonValue::Array(vec![JsonValue::Null])[1];
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index string with 0")]
+    fn index_scalar() {
+        let _ = &JsonValue::String("a".to_string())[0];
+    }
+
+    #[test]
+    fn null() {
+        assert_eq!(JsonValue::Null.as_null(), Some(()));
+        assert_eq!(JsonValue::Boolean(false).as_null(), None);
+        assert_eq!(JsonValue::String("null".to_string()).as_null(), None);
+
+        assert_eq!(
+            JsonValue::Null.null_or(JsonValue::as_i64_coerced),
+            Some(None)
+        );
+        assert_eq!(
+            JsonValue::Number(5.into()).null_or(JsonValue::as_i64_coerced),
+            Some(Some(5))
+        );
+        assert_eq!(
+            JsonValue::Boolean(true).null_or(JsonValue::as_i64_coerced),
+            None
+        );
+    }
+
+    #[test]
+    fn try_get() {
+        let value = Parser::new(Tokenizer::new(r#"{"a":{"b/c":[1],"d":null}}"#.chars()))
+            .parse()
+            .unwrap();
+        let inner = value.try_get("a").unwrap();
+        assert_eq!(inner.try_get("d"), Ok(&JsonValue::Null));
+        assert_eq!(
+            inner.try_get("x"),
+            Err(ParseError::MissingKey {
+                key: "x".to_string()
+            })
+        );
+        assert_eq!(
+            JsonValue::Array(vec![])
+                .try_get("a")
+                .unwrap_err()
+                .to_string(),
+            "expected an object, found array"
+        );
+
+        assert_eq!(value.try_get_path(&[]), Ok(&value));
+        assert_eq!(
+            value.try_get_path(&["a", "b/c"]),
+            Ok(&JsonValue::Array(vec![JsonValue::Number(1.into())]))
+        );
+        assert_eq!(
+            value.try_get_path(&["x", "y"]).unwrap_err().to_string(),
+            r#"missing key "x""#
+        );
+        let error = value.try_get_path(&["a", "b/c", "e"]).unwrap_err();
+        assert_eq!(error.path(), Some("/a/b~1c"));
+        assert_eq!(
+            error.to_string(),
+            "expected an object, found array in /a/b~1c"
+        );
+    }
+}
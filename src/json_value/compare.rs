@@ -0,0 +1,148 @@
+//! Comparisons between JSON values that are looser than `==`.
+
+use crate::json_value::{member, JsonValue};
+
+impl JsonValue {
+    /// Compares two values like `==`, except that numbers only need to be within `epsilon` of
+    /// each other. Objects must still have the same keys in the same order.
+    pub fn approx_eq(&self, other: &JsonValue, epsilon: f64) -> bool {
+        let mut stack = vec![(self, other)];
+        while let Some(pair) = stack.pop() {
+            match pair {
+                (JsonValue::Number(a), JsonValue::Number(b)) => match (a.as_f64(), b.as_f64()) {
+                    (Some(a), Some(b)) => {
+                        if (a - b).abs() > epsilon {
+                            return false;
+                        }
+                    }
+                    _ => {
+                        if a != b {
+                            return false;
+                        }
+                    }
+                },
+                (JsonValue::Array(a), JsonValue::Array(b)) => {
+                    if a.len() != b.len() {
+                        return false;
+                    }
+                    stack.extend(a.iter().zip(b));
+                }
+                (JsonValue::Object(a), JsonValue::Object(b)) => {
+                    if a.len() != b.len() {
+                        return false;
+                    }
+                    for ((a_key, a_member), (b_key, b_member)) in a.iter().zip(b) {
+                        if a_key != b_key {
+                            return false;
+                        }
+                        stack.push((a_member, b_member));
+                    }
+                }
+                (a, b) => {
+                    if a != b {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Whether `self` contains at least what `subset` holds, e.g. to check that a response has
+    /// the expected fields without spelling out all of them. Objects must contain every key of
+    /// the subset with a value that is again a superset of the subset's one, extra keys are
+    /// ignored. Arrays must contain the subset's elements as a subsequence: in the same order,
+    /// but other elements may come before, between and after them. Scalars must be equal.
+    pub fn is_superset_of(&self, subset: &JsonValue) -> bool {
+        match (self, subset) {
+            (JsonValue::Object(members), JsonValue::Object(subset_members)) => {
+                subset_members.iter().all(|(key, subset_member)| {
+                    member(members, key).is_some_and(|member| member.is_superset_of(subset_member))
+                })
+            }
+            (JsonValue::Array(elements), JsonValue::Array(subset_elements)) => {
+                // Matching every subset element to the first fitting element is enough, a later
+                // one would only leave fewer candidates for the rest.
+                let mut elements = elements.iter();
+                subset_elements.iter().all(|subset_element| {
+                    elements.any(|element| element.is_superset_of(subset_element))
+                })
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Number, Object};
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn approx_eq() {
+        let a = JsonValue::Object(Object::from_iter([
+            ("x".to_string(), JsonValue::Number(100.into())),
+            (
+                "list".to_string(),
+                JsonValue::Array(vec![JsonValue::Number(1.into()), JsonValue::Null]),
+            ),
+        ]));
+        let b = JsonValue::Object(Object::from_iter([
+            ("x".to_string(), JsonValue::Number(101.into())),
+            (
+                "list".to_string(),
+                JsonValue::Array(vec![JsonValue::Number(2.into()), JsonValue::Null]),
+            ),
+        ]));
+        assert!(a.approx_eq(&b, 1.0));
+        assert!(!a.approx_eq(&b, 0.5));
+        assert!(a.approx_eq(&a, 0.0));
+
+        // Everything but numbers must match exactly.
+        let c = JsonValue::Array(vec![JsonValue::String("1".to_string())]);
+        let d = JsonValue::Array(vec![JsonValue::Number(1.into())]);
+        assert!(!c.approx_eq(&d, 10.0));
+        let e = JsonValue::Array(vec![
+            JsonValue::Number(1.into()),
+            JsonValue::Number(2.into()),
+        ]);
+        assert!(!d.approx_eq(&e, 10.0));
+
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse().unwrap();
+        let sum = JsonValue::Number(Number::from_f64(0.1 + 0.2).unwrap());
+        assert_ne!(sum, parse("0.3"));
+        assert!(sum.approx_eq(&parse("0.3"), 1e-9));
+        assert!(!sum.approx_eq(&parse("0.31"), 1e-9));
+    }
+
+    #[test]
+    fn is_superset_of() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse().unwrap();
+        let response = parse(
+            r#"{"id":7,"user":{"name":"a","roles":["admin","dev","ops"]},"tags":[1,2,3],"ok":true}"#,
+        );
+        for subset in [
+            r#"{}"#,
+            r#"{"id":7}"#,
+            r#"{"user":{"roles":["admin","ops"]},"ok":true}"#,
+            r#"{"tags":[1,3]}"#,
+            r#"{"tags":[]}"#,
+        ] {
+            assert!(response.is_superset_of(&parse(subset)), "{}", subset);
+        }
+        for subset in [
+            r#"{"id":8}"#,
+            r#"{"missing":null}"#,
+            r#"{"user":{"roles":["ops","admin"]}}"#,
+            r#"{"tags":[1,1]}"#,
+            r#"{"tags":{"0":1}}"#,
+            r#"[]"#,
+        ] {
+            assert!(!response.is_superset_of(&parse(subset)), "{}", subset);
+        }
+        assert!(parse("[[1,2],[3]]").is_superset_of(&parse("[[2],[3]]")));
+        assert!(parse("1").is_superset_of(&parse("1")));
+        assert!(!parse("1").is_superset_of(&parse("[1]")));
+    }
+}
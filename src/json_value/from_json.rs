@@ -0,0 +1,96 @@
+//! Conversion of JSON values into Rust types.
+
+use crate::json_value::{member, JsonValue};
+
+/// Types that can be extracted from a `JsonValue`. Returns `None` if the value is of a
+/// different type or does not fit.
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Option<Self>;
+}
+
+impl FromJson for i64 {
+    fn from_json(value: &JsonValue) -> Option<i64> {
+        match value {
+            JsonValue::Number(number) => number.as_i64(),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Option<f64> {
+        match value {
+            JsonValue::Number(number) => number.as_f64(),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Option<bool> {
+        match value {
+            JsonValue::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Option<String> {
+        match value {
+            JsonValue::String(string) => Some(string.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Option<Vec<T>> {
+        match value {
+            JsonValue::Array(elements) => elements.iter().map(T::from_json).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl JsonValue {
+    /// Looks up `key` in an object and converts the member to `T`. Returns `None` if the value
+    /// is not an object, the key is missing, or the member is of a different type. With
+    /// duplicate keys the first occurrence is used.
+    pub fn get_typed<T: FromJson>(&self, key: &str) -> Option<T> {
+        let JsonValue::Object(members) = self else {
+            return None;
+        };
+        member(members, key).and_then(T::from_json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+
+    #[test]
+    fn get_typed() {
+        let object = JsonValue::Object(Object::from_iter([
+            ("id".to_string(), JsonValue::Number(7.into())),
+            ("name".to_string(), JsonValue::String("seven".to_string())),
+            (
+                "tags".to_string(),
+                JsonValue::Array(vec![JsonValue::String("odd".to_string())]),
+            ),
+        ]));
+        assert_eq!(object.get_typed::<i64>("id"), Some(7));
+        assert_eq!(
+            object.get_typed::<String>("name"),
+            Some("seven".to_string())
+        );
+        assert_eq!(
+            object.get_typed::<Vec<String>>("tags"),
+            Some(vec!["odd".to_string()])
+        );
+
+        assert_eq!(object.get_typed::<String>("id"), None);
+        assert_eq!(object.get_typed::<i64>("missing"), None);
+        assert_eq!(JsonValue::Null.get_typed::<i64>("id"), None);
+    }
+}
@@ -0,0 +1,110 @@
+//! Hashing of JSON values that is stable across runs and platforms.
+
+use crate::json_value::JsonValue;
+
+// FNV-1a, written out so the result does not depend on the standard library's hasher.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    // Writes a tag and a length-prefixed payload, so that different values can never produce the
+    // same byte sequence.
+    fn write_item(&mut self, tag: u8, payload: &[u8]) {
+        self.write(&[tag]);
+        self.write(&(payload.len() as u64).to_le_bytes());
+        self.write(payload);
+    }
+}
+
+enum Item<'a> {
+    Value(&'a JsonValue),
+    Key(&'a str),
+}
+
+impl JsonValue {
+    /// A hash of the value that is the same in every run and on every platform, e.g. for cache
+    /// keys. Object members are hashed in key order, so documents that only differ in the order
+    /// of their keys hash the same. Numbers are hashed by the decimal text of their value, so
+    /// `1e3` and `1000` hash the same. Other numbers are hashed by their value as a float, or as
+    /// written if they are too large for one.
+    pub fn stable_hash(&self) -> u64 {
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+        let mut stack = vec![Item::Value(self)];
+        while let Some(item) = stack.pop() {
+            let value = match item {
+                Item::Key(key) => {
+                    hasher.write_item(b'k', key.as_bytes());
+                    continue;
+                }
+                Item::Value(value) => value,
+            };
+
+            match value {
+                JsonValue::Null => hasher.write_item(b'n', &[]),
+                JsonValue::Boolean(boolean) => hasher.write_item(b'b', &[*boolean as u8]),
+                JsonValue::Number(number) => match (number.as_i64(), number.as_f64()) {
+                    (Some(integer), _) => hasher.write_item(b'i', integer.to_string().as_bytes()),
+                    (None, Some(float)) => {
+                        hasher.write_item(b'f', format!("{:?}", float).as_bytes())
+                    }
+                    (None, None) => hasher.write_item(b'i', number.to_string().as_bytes()),
+                },
+                JsonValue::String(string) => hasher.write_item(b's', string.as_bytes()),
+                JsonValue::Array(elements) => {
+                    hasher.write_item(b'a', &(elements.len() as u64).to_le_bytes());
+                    stack.extend(elements.iter().rev().map(Item::Value));
+                }
+                JsonValue::Object(members) => {
+                    hasher.write_item(b'o', &(members.len() as u64).to_le_bytes());
+                    let mut members: Vec<_> = members.iter().collect();
+                    // Stable, so duplicate keys are still hashed in document order.
+                    members.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+                    for (key, member) in members.into_iter().rev() {
+                        stack.push(Item::Value(member));
+                        stack.push(Item::Key(key));
+                    }
+                }
+            }
+        }
+        hasher.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn stable_hash() {
+        let hash = |input: &str| {
+            Parser::new(Tokenizer::new(input.chars()))
+                .parse()
+                .unwrap()
+                .stable_hash()
+        };
+        let a = hash(r#"{"name":"x","tags":[1,2],"meta":{"a":true,"b":null}}"#);
+        let b = hash(r#"{"meta":{"b":null,"a":true},"tags":[1,2],"name":"x"}"#);
+        assert_eq!(a, b);
+
+        assert_ne!(
+            a,
+            hash(r#"{"name":"x","tags":[2,1],"meta":{"a":true,"b":null}}"#)
+        );
+        assert_ne!(hash(r#"["ab","c"]"#), hash(r#"["a","bc"]"#));
+        assert_ne!(hash(r#"{"a":"b"}"#), hash(r#"["a","b"]"#));
+        assert_ne!(hash("1"), hash(r#""1""#));
+
+        // Pinned, so an accidental change to the algorithm shows up.
+        assert_eq!(hash("null"), 0xc7cd_2e1e_3390_b091);
+    }
+}
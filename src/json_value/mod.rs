@@ -0,0 +1,213 @@
+mod accessors;
+mod compare;
+mod from_json;
+mod hash;
+pub(crate) mod number;
+mod pointer;
+mod transform;
+mod traverse;
+
+pub use crate::json_value::from_json::FromJson;
+pub use crate::json_value::number::Number;
+pub use crate::json_value::traverse::Stats;
+use std::fmt;
+
+// All possible JSON values as defined by the RFC-8259 standard.
+// https://www.rfc-editor.org/rfc/rfc8259.html#section-3
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(String),
+    Array(Array),
+    Object(Object),
+}
+
+pub type Array = Vec<JsonValue>;
+
+/// Object members in document order. Lookups scan the members and return the first occurrence
+/// of a key, which only matters for objects parsed with `DuplicateKeys::Keep`.
+#[cfg(not(feature = "indexmap"))]
+pub type Object = Vec<(String, JsonValue)>;
+
+/// Object members in document order, with lookups by key in constant time. Keys are unique, so
+/// `DuplicateKeys::Keep` behaves like `DuplicateKeys::KeepLast`: a repeated key replaces the
+/// value of the first occurrence and keeps its position. Objects compare equal when they have
+/// the same members in any order.
+#[cfg(feature = "indexmap")]
+pub type Object = indexmap::IndexMap<String, JsonValue>;
+
+// The first member named `key`, found by a scan of the members.
+#[cfg(not(feature = "indexmap"))]
+pub(crate) fn member<'a>(members: &'a Object, key: &str) -> Option<&'a JsonValue> {
+    members
+        .iter()
+        .find(|(member_key, _)| member_key == key)
+        .map(|(_, member)| member)
+}
+
+// The member named `key`, found by its hash.
+#[cfg(feature = "indexmap")]
+pub(crate) fn member<'a>(members: &'a Object, key: &str) -> Option<&'a JsonValue> {
+    members.get(key)
+}
+
+#[cfg(not(feature = "indexmap"))]
+pub(crate) fn member_mut<'a>(members: &'a mut Object, key: &str) -> Option<&'a mut JsonValue> {
+    members
+        .iter_mut()
+        .find(|(member_key, _)| member_key == key)
+        .map(|(_, member)| member)
+}
+
+#[cfg(feature = "indexmap")]
+pub(crate) fn member_mut<'a>(members: &'a mut Object, key: &str) -> Option<&'a mut JsonValue> {
+    members.get_mut(key)
+}
+
+/// The kind of a `JsonValue`, without its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl JsonValue {
+    /// Appends a value to the end of an array.
+    ///
+    /// Panics if the value is not an array.
+    pub fn push(&mut self, value: JsonValue) {
+        match self {
+            JsonValue::Array(elements) => elements.push(value),
+            _ => panic!("cannot push onto a non-array JSON value"),
+        }
+    }
+
+    /// Removes the last element of an array. Returns `None` if the array is empty or the value
+    /// is not an array.
+    pub fn pop(&mut self) -> Option<JsonValue> {
+        match self {
+            JsonValue::Array(elements) => elements.pop(),
+            _ => None,
+        }
+    }
+}
+
+/// Appends members to an object.
+///
+/// Panics if the value is not an object.
+impl Extend<(String, JsonValue)> for JsonValue {
+    fn extend<T: IntoIterator<Item = (String, JsonValue)>>(&mut self, iter: T) {
+        match self {
+            JsonValue::Object(members) => members.extend(iter),
+            _ => panic!("cannot extend a non-object JSON value with members"),
+        }
+    }
+}
+
+/// Appends elements to an array.
+///
+/// Panics if the value is not an array.
+impl Extend<JsonValue> for JsonValue {
+    fn extend<T: IntoIterator<Item = JsonValue>>(&mut self, iter: T) {
+        match self {
+            JsonValue::Array(elements) => elements.extend(iter),
+            _ => panic!("cannot extend a non-array JSON value with elements"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+
+    #[test]
+    fn extend() {
+        let mut object = JsonValue::Object(Object::from_iter([(
+            "a".to_string(),
+            JsonValue::Number(1.into()),
+        )]));
+        object.extend(vec![
+            ("b".to_string(), JsonValue::Number(2.into())),
+            ("c".to_string(), JsonValue::Null),
+        ]);
+        assert_eq!(
+            object,
+            JsonValue::Object(Object::from_iter([
+                ("a".to_string(), JsonValue::Number(1.into())),
+                ("b".to_string(), JsonValue::Number(2.into())),
+                ("c".to_string(), JsonValue::Null),
+            ]))
+        );
+
+        let mut array = JsonValue::Array(vec![JsonValue::Boolean(true)]);
+        array.extend(vec![
+            JsonValue::Number(5.into()),
+            JsonValue::String("x".to_string()),
+        ]);
+        assert_eq!(
+            array,
+            JsonValue::Array(vec![
+                JsonValue::Boolean(true),
+                JsonValue::Number(5.into()),
+                JsonValue::String("x".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let mut array = JsonValue::Array(vec![]);
+        array.push(JsonValue::Number(1.into()));
+        array.push(JsonValue::String("two".to_string()));
+        array.push(JsonValue::Null);
+        assert_eq!(
+            array,
+            JsonValue::Array(vec![
+                JsonValue::Number(1.into()),
+                JsonValue::String("two".to_string()),
+                JsonValue::Null,
+            ])
+        );
+
+        assert_eq!(array.pop(), Some(JsonValue::Null));
+        assert_eq!(array.pop(), Some(JsonValue::String("two".to_string())));
+        assert_eq!(array.pop(), Some(JsonValue::Number(1.into())));
+        assert_eq!(array.pop(), None);
+        assert_eq!(JsonValue::Number(1.into()).pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_mismatch() {
+        let mut object = JsonValue::Object(Object::new());
+        object.push(JsonValue::Null);
+    }
+
+    #[test]
+    #[should_panic]
+    fn extend_mismatch() {
+        let mut array = JsonValue::Array(vec![]);
+        array.extend(vec![("a".to_string(), JsonValue::Null)]);
+    }
+}
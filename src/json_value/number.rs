@@ -0,0 +1,287 @@
+//! The numeric payload of `JsonValue::Number`.
+
+use std::cell::OnceCell;
+use std::fmt;
+
+/// A JSON number, either an integer or a finite float. Numbers too large for an i64 or f64 keep
+/// the text they were written as, so they are serialized again without loss. Numbers parsed with
+/// `ParseOptions::lazy_numbers` keep their text as well and are only converted when read, the
+/// converted value is cached. Either way they behave exactly like eagerly parsed numbers.
+#[derive(Debug, Clone)]
+pub struct Number(Repr);
+
+#[derive(Debug, Clone)]
+enum Repr {
+    Integer(i64),
+    Float(f64),
+    // The number as written, and its value once it was read.
+    Lazy(String, OnceCell<Decoded>),
+}
+
+// The value of a number as the parser would have stored it when reading it eagerly.
+#[derive(Debug, Clone, Copy)]
+enum Decoded {
+    Integer(i64),
+    Float(f64),
+    // Too large for an i64, or for an f64 if it is not an integer, so only the text is exact.
+    // Holds the nearest float, if there is one.
+    Wide(Option<f64>),
+}
+
+#[derive(PartialEq)]
+enum Key<'a> {
+    Integer(i128),
+    Float(f64),
+    Text(&'a str),
+}
+
+impl Number {
+    // Expects a lexeme the tokenizer produced, the value is only checked when it is read.
+    pub(crate) fn lazy(lexeme: String) -> Number {
+        Number(Repr::Lazy(lexeme, OnceCell::new()))
+    }
+
+    /// JSON has no representation for NaN and infinities, so `None` is returned for them.
+    pub fn from_f64(number: f64) -> Option<Number> {
+        number.is_finite().then_some(Number(Repr::Float(number)))
+    }
+
+    /// Integers above `i64::MAX` are kept as their text, which `as_u64` reads back without loss.
+    pub fn from_u64(number: u64) -> Number {
+        match i64::try_from(number) {
+            Ok(number) => Number(Repr::Integer(number)),
+            Err(_) => Number(Repr::Lazy(
+                number.to_string(),
+                OnceCell::from(Decoded::Wide(Some(number as f64))),
+            )),
+        }
+    }
+
+    /// The value of the number. Returns `None` for numbers with a fractional part and integers
+    /// that do not fit in an i64. Floats with an integral value such as `1e3` are converted.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.decoded() {
+            Decoded::Integer(number) => Some(number),
+            Decoded::Float(number) => i64::try_from(float_to_integer(number)?).ok(),
+            Decoded::Wide(_) => None,
+        }
+    }
+
+    /// Like `as_i64`, for non-negative integers up to `u64::MAX`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match (&self.0, self.decoded()) {
+            (_, Decoded::Integer(number)) => u64::try_from(number).ok(),
+            (_, Decoded::Float(number)) => u64::try_from(float_to_integer(number)?).ok(),
+            (Repr::Lazy(lexeme, _), Decoded::Wide(_)) => {
+                u64::try_from(parse_wide_integer(lexeme)?).ok()
+            }
+            (_, Decoded::Wide(_)) => None,
+        }
+    }
+
+    /// The value as a float, which may round integers with more than 53 significant bits.
+    /// Returns `None` only for numbers too large for an f64.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.decoded() {
+            Decoded::Integer(number) => Some(number as f64),
+            Decoded::Float(number) => Some(number),
+            Decoded::Wide(number) => number,
+        }
+    }
+
+    /// Whether the number was written as an integer, without a fraction or an exponent.
+    pub fn is_integer(&self) -> bool {
+        match &self.0 {
+            Repr::Integer(_) => true,
+            Repr::Float(_) => false,
+            Repr::Lazy(lexeme, _) => !lexeme.contains(['.', 'e', 'E']),
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        !self.is_integer()
+    }
+
+    // What equality compares: the exact integer value if there is one, else the float value or,
+    // failing that, the text.
+    fn key(&self) -> Key<'_> {
+        // Only lazily stored numbers can be wide.
+        let lexeme = match &self.0 {
+            Repr::Lazy(lexeme, _) => lexeme,
+            _ => "",
+        };
+        match self.decoded() {
+            Decoded::Integer(number) => Key::Integer(number.into()),
+            Decoded::Float(number) if number.fract() == 0.0 && number.abs() < i128::MAX as f64 => {
+                Key::Integer(number as i128)
+            }
+            Decoded::Float(number) => Key::Float(number),
+            Decoded::Wide(_) => parse_wide_integer(lexeme).map_or(Key::Text(lexeme), Key::Integer),
+        }
+    }
+
+    fn decoded(&self) -> Decoded {
+        match &self.0 {
+            Repr::Integer(number) => Decoded::Integer(*number),
+            Repr::Float(number) => Decoded::Float(*number),
+            Repr::Lazy(lexeme, value) => *value.get_or_init(|| decode(lexeme)),
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(number: i64) -> Number {
+        Number(Repr::Integer(number))
+    }
+}
+
+/// Numbers are equal if they have the same value, however they were written, so `1e3` equals
+/// `1000`. Floats without an integral value are compared as floats, and numbers too large to
+/// convert exactly by their text.
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.key() == other.key()
+    }
+}
+
+/// Lazily parsed numbers are written exactly as they appeared in the input. Floats are written in
+/// the shortest form that reads back as the same value, always with a fraction or an exponent so
+/// they stay floats, e.g. `1.0`, `0.1` or `1e100`.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Integer(number) => write!(f, "{}", number),
+            Repr::Float(number) => write!(f, "{:?}", number),
+            Repr::Lazy(lexeme, _) => write!(f, "{}", lexeme),
+        }
+    }
+}
+
+// Decodes a lexeme the way the parser does when it reads numbers eagerly.
+fn decode(lexeme: &str) -> Decoded {
+    let float = lexeme
+        .parse()
+        .ok()
+        .filter(|number: &f64| number.is_finite());
+    if lexeme.contains(['.', 'e', 'E']) {
+        return float.map_or(Decoded::Wide(None), Decoded::Float);
+    }
+    parse_integer(lexeme).map_or(Decoded::Wide(float), Decoded::Integer)
+}
+
+// The value of a float without a fractional part, if it is within the range of a u64 or an i64.
+fn float_to_integer(number: f64) -> Option<i128> {
+    // 2^64, above which the conversion would saturate.
+    (number.fract() == 0.0 && number.abs() < 18446744073709551616.0).then_some(number as i128)
+}
+
+// The lexeme may carry an exponent such as `1e3`, whose effective value still has to fit in an
+// i64.
+pub(crate) fn parse_integer(lexeme: &str) -> Option<i64> {
+    i64::try_from(parse_wide_integer(lexeme)?).ok()
+}
+
+// Like `parse_integer`, with room for every u64 as well.
+fn parse_wide_integer(lexeme: &str) -> Option<i128> {
+    let Some((mantissa, exponent)) = lexeme.split_once(['e', 'E']) else {
+        return lexeme.parse().ok();
+    };
+
+    let mantissa: i128 = mantissa.parse().ok()?;
+    let exponent: i32 = exponent.parse().ok()?;
+    let scale = 10i128.checked_pow(exponent.unsigned_abs())?;
+    if exponent >= 0 {
+        mantissa.checked_mul(scale)
+    } else if mantissa % scale == 0 {
+        Some(mantissa / scale)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::Number;
+
+    #[test]
+    fn lazy() {
+        let number = Number::lazy("15e2".to_string());
+        assert_eq!(number.as_i64(), Some(1500));
+        assert_eq!(number.as_i64(), Some(1500));
+        assert_eq!(number, Number::from(1500));
+        assert_eq!(number.to_string(), "15e2");
+
+        let too_large = Number::lazy("1e19".to_string());
+        assert_eq!(too_large.as_i64(), None);
+        assert_eq!(too_large, Number::lazy("1e19".to_string()));
+        assert_ne!(too_large, Number::lazy("1e20".to_string()));
+    }
+
+    #[test]
+    fn integers() {
+        let number = Number::from(-42);
+        assert!(number.is_integer() && !number.is_float());
+        assert_eq!(number.as_i64(), Some(-42));
+        assert_eq!(number.as_u64(), None);
+        assert_eq!(number.as_f64(), Some(-42.0));
+        assert_eq!(number.to_string(), "-42");
+
+        let exponent = Number::lazy("25e-1".to_string());
+        assert!(exponent.is_float());
+        assert_eq!(exponent.as_i64(), None);
+        assert_eq!(exponent.as_f64(), Some(2.5));
+        let exponent = Number::lazy("1e3".to_string());
+        assert!(exponent.is_float());
+        assert_eq!(exponent.as_u64(), Some(1000));
+    }
+
+    #[test]
+    fn floats() {
+        let number = Number::from_f64(1.5).unwrap();
+        assert!(number.is_float() && !number.is_integer());
+        assert_eq!(number.as_f64(), Some(1.5));
+        assert_eq!((number.as_i64(), number.as_u64()), (None, None));
+        assert_eq!(number, Number::from_f64(1.5).unwrap());
+        assert_eq!(number, Number::lazy("15e-1".to_string()));
+
+        // Integral floats convert, and equal the integer.
+        let integral = Number::from_f64(-1e3).unwrap();
+        assert_eq!((integral.as_i64(), integral.as_u64()), (Some(-1000), None));
+        assert_eq!(integral, Number::from(-1000));
+        assert_eq!(integral.to_string(), "-1000.0");
+        assert_eq!(Number::from_f64(1e20).unwrap().as_u64(), None);
+
+        for (number, text) in [(1.5, "1.5"), (1.0, "1.0"), (-0.1, "-0.1"), (1e100, "1e100")] {
+            assert_eq!(Number::from_f64(number).unwrap().to_string(), text);
+        }
+        assert!(Number::from_f64(f64::NAN).is_none());
+        assert!(Number::from_f64(f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn large_numbers() {
+        let number = Number::from_u64(u64::MAX);
+        assert!(number.is_integer());
+        assert_eq!(number.as_i64(), None);
+        assert_eq!(number.as_u64(), Some(u64::MAX));
+        assert_eq!(number.as_f64(), Some(u64::MAX as f64));
+        assert_eq!(number.to_string(), "18446744073709551615");
+        assert_eq!(number, Number::lazy("18446744073709551615".to_string()));
+        assert_eq!(Number::from_u64(7), Number::from(7));
+
+        let too_large = Number::lazy("1e400".to_string());
+        assert_eq!((too_large.as_u64(), too_large.as_f64()), (None, None));
+
+        // Compared by their exact value where possible.
+        let wide = Number::lazy("10000000000000000000".to_string());
+        assert_eq!(wide, Number::from_f64(1e19).unwrap());
+        assert_ne!(
+            Number::lazy("10000000000000000001".to_string()),
+            Number::from_f64(1e19).unwrap()
+        );
+        assert_ne!(
+            Number::from(i64::MAX),
+            Number::from_f64(2f64.powi(63)).unwrap()
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Lookups of nested values by JSON Pointer, as defined by RFC 6901.
+//! https://www.rfc-editor.org/rfc/rfc6901
+
+use crate::json_value::{member_mut, JsonValue};
+
+impl JsonValue {
+    /// Looks up the value a JSON Pointer such as `/foo/0/bar` refers to, with `~1` standing for
+    /// `/` and `~0` for `~` in keys. The empty pointer refers to the whole value. Returns `None`
+    /// if a key or index along the way does not exist. With duplicate keys the first occurrence
+    /// is used.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        let mut value = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = unescape_token(token);
+            value = match value {
+                JsonValue::Object(_) => value.get(&token)?,
+                JsonValue::Array(_) => value.get_index(parse_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    // The empty pointer refers to the value itself, every `/` descends into a member or element.
+    pub(crate) fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        let mut value = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = unescape_token(token);
+            value = match value {
+                JsonValue::Object(members) => member_mut(members, &token)?,
+                JsonValue::Array(elements) => elements.get_mut(parse_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+// `~1` stands for `/` and `~0` for `~`, replaced in this order so `~01` becomes `~1`.
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+// Array indices are plain decimal numbers without leading zeros.
+fn parse_index(token: &str) -> Option<usize> {
+    if !token.bytes().all(|byte| byte.is_ascii_digit()) || (token.starts_with('0') && token != "0")
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::JsonValue;
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn pointer() {
+        let json_str = r#"{"foo":[{"bar":1},2],"a/b":3,"m~n":4,"":5,"0":{"01":6}}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        let number = |number: i64| Some(JsonValue::Number(number.into()));
+
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(value.pointer("/foo/0/bar").cloned(), number(1));
+        assert_eq!(value.pointer("/foo/1").cloned(), number(2));
+        assert_eq!(value.pointer("/a~1b").cloned(), number(3));
+        assert_eq!(value.pointer("/m~0n").cloned(), number(4));
+        assert_eq!(value.pointer("/").cloned(), number(5));
+        assert_eq!(value.pointer("/0/01").cloned(), number(6));
+
+        for missing in [
+            "/missing",
+            "/foo/2",
+            "/foo/01",
+            "/foo/-",
+            "/foo/0/bar/x",
+            "foo",
+        ] {
+            assert_eq!(value.pointer(missing), None, "{}", missing);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! In-place transformations of nested JSON values.
+
+use crate::error::ParseError;
+use crate::json_value::{JsonType, JsonValue, Object};
+use crate::parser::Parser;
+use crate::tokenizer::Tokenizer;
+#[cfg(not(feature = "indexmap"))]
+use std::collections::HashSet;
+
+impl JsonValue {
+    /// Shortens every string value longer than `max_len` bytes to at most `max_len` bytes, cut on
+    /// a character boundary, and appends `…` to mark it as truncated. Object keys are left
+    /// intact. Handy before writing large payloads to logs.
+    pub fn truncate_strings(&mut self, max_len: usize) {
+        self.map_leaves(|value| {
+            if let JsonValue::String(string) = value {
+                if string.len() > max_len {
+                    let mut end = max_len;
+                    while !string.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    string.truncate(end);
+                    string.push('…');
+                }
+            }
+        });
+    }
+
+    /// Renames object keys, at any depth, for which `f` returns a new name. If an object ends up
+    /// with the same key more than once, only the first of those members is kept, which is the
+    /// one lookups would have returned anyway. Objects without renamed keys are left untouched.
+    pub fn rename_keys(&mut self, f: impl Fn(&str) -> Option<String>) {
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            match value {
+                JsonValue::Array(elements) => stack.extend(elements.iter_mut()),
+                JsonValue::Object(members) => {
+                    rename_members(members, &f);
+                    stack.extend(members.iter_mut().map(|(_, member)| member));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Sorts the members of every object by key, at any depth. The sort is stable, so duplicate
+    /// keys keep their relative order.
+    pub fn sort_keys(&mut self) {
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            match value {
+                JsonValue::Array(elements) => stack.extend(elements.iter_mut()),
+                JsonValue::Object(members) => {
+                    sort_members(members);
+                    stack.extend(members.iter_mut().map(|(_, member)| member));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns a copy with the object keys sorted like `sort_keys`, leaving `self` untouched.
+    pub fn sorted(&self) -> JsonValue {
+        let mut sorted = self.clone();
+        sorted.sort_keys();
+        sorted
+    }
+
+    /// Converts the value at the JSON Pointer `pointer` to `target`, e.g. to migrate a field
+    /// whose type changed. Numbers and booleans can become strings, and strings holding a valid
+    /// JSON number such as `42` or `-1.5e3`, or `true`/`false`, can be turned back. Converting a
+    /// value to its own type leaves it untouched, any other conversion is an error.
+    pub fn coerce_at_pointer(&mut self, pointer: &str, target: JsonType) -> Result<(), ParseError> {
+        let value = self
+            .pointer_mut(pointer)
+            .ok_or_else(|| ParseError::PointerNotFound {
+                pointer: pointer.to_string(),
+            })?;
+        let coerced = match (&*value, target) {
+            (value, target) if value.json_type() == target => return Ok(()),
+            (JsonValue::Number(number), JsonType::String) => {
+                Some(JsonValue::String(number.to_string()))
+            }
+            (JsonValue::Boolean(boolean), JsonType::String) => {
+                Some(JsonValue::String(boolean.to_string()))
+            }
+            // Read with the parser, so the string must be exactly one number.
+            (JsonValue::String(string), JsonType::Number) => {
+                match Parser::new(Tokenizer::new(string.chars())).parse() {
+                    Ok(number @ JsonValue::Number(_)) if string.trim() == string => Some(number),
+                    _ => None,
+                }
+            }
+            (JsonValue::String(string), JsonType::Boolean) => match string.as_str() {
+                "true" => Some(JsonValue::Boolean(true)),
+                "false" => Some(JsonValue::Boolean(false)),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match coerced {
+            Some(coerced) => {
+                *value = coerced;
+                Ok(())
+            }
+            None => Err(ParseError::InvalidConversion {
+                pointer: pointer.to_string(),
+                from: value.json_type(),
+                to: target,
+            }),
+        }
+    }
+
+    /// Removes leading and trailing ASCII whitespace from object keys at any depth, so
+    /// `" a "` can be looked up as `"a"`. Keys that become equal are handled like in
+    /// `rename_keys`, the first member is kept.
+    pub fn trim_keys(&mut self) {
+        self.rename_keys(|key| {
+            let trimmed = key.trim_matches(|ch: char| ch.is_ascii_whitespace());
+            (trimmed.len() != key.len()).then(|| trimmed.to_string())
+        });
+    }
+}
+
+#[cfg(not(feature = "indexmap"))]
+fn rename_members(members: &mut Object, f: &impl Fn(&str) -> Option<String>) {
+    let mut renamed = false;
+    for (key, _) in members.iter_mut() {
+        if let Some(new_key) = f(key) {
+            *key = new_key;
+            renamed = true;
+        }
+    }
+    if renamed {
+        let mut seen = HashSet::new();
+        members.retain(|(key, _)| seen.insert(key.clone()));
+    }
+}
+
+// Keys of an `IndexMap` cannot be changed in place, so the members are moved to a new one.
+#[cfg(feature = "indexmap")]
+fn rename_members(members: &mut Object, f: &impl Fn(&str) -> Option<String>) {
+    let mut renamed = Object::with_capacity(members.len());
+    for (key, member) in members.drain(..) {
+        let key = f(&key).unwrap_or(key);
+        renamed.entry(key).or_insert(member);
+    }
+    *members = renamed;
+}
+
+#[cfg(not(feature = "indexmap"))]
+fn sort_members(members: &mut Object) {
+    members.sort_by(|a, b| a.0.cmp(&b.0));
+}
+
+#[cfg(feature = "indexmap")]
+fn sort_members(members: &mut Object) {
+    members.sort_keys();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ParseError;
+    use crate::json_value::{JsonType, JsonValue, Object};
+    use crate::parser::{DuplicateKeys, ParseOptions, Parser};
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn truncate_strings() {
+        let mut value = JsonValue::Object(Object::from_iter([
+            (
+                "a rather long key".to_string(),
+                JsonValue::String("short".to_string()),
+            ),
+            (
+                "list".to_string(),
+                JsonValue::Array(vec![
+                    JsonValue::String("this one is too long".to_string()),
+                    JsonValue::String("exactly10!".to_string()),
+                    JsonValue::String("héééééééé".to_string()),
+                    JsonValue::Number(12345678901.into()),
+                ]),
+            ),
+        ]));
+        value.truncate_strings(10);
+        assert_eq!(
+            value,
+            JsonValue::Object(Object::from_iter([
+                (
+                    "a rather long key".to_string(),
+                    JsonValue::String("short".to_string()),
+                ),
+                (
+                    "list".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::String("this one i…".to_string()),
+                        JsonValue::String("exactly10!".to_string()),
+                        // Cutting at ten bytes would split an "é".
+                        JsonValue::String("héééé…".to_string()),
+                        JsonValue::Number(12345678901.into()),
+                    ]),
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rename_keys() {
+        let to_snake_case = |key: &str| {
+            if !key.contains(char::is_uppercase) {
+                return None;
+            }
+            let mut snake = String::new();
+            for ch in key.chars() {
+                if ch.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.extend(ch.to_lowercase());
+            }
+            Some(snake)
+        };
+
+        let mut value = JsonValue::Object(Object::from_iter([
+            ("userId".to_string(), JsonValue::Number(1.into())),
+            (
+                "recentOrders".to_string(),
+                JsonValue::Array(vec![JsonValue::Object(Object::from_iter([
+                    ("orderId".to_string(), JsonValue::Number(2.into())),
+                    ("total".to_string(), JsonValue::Number(3.into())),
+                ]))]),
+            ),
+            // Collides with the renamed "userId" and is dropped.
+            ("user_id".to_string(), JsonValue::Number(4.into())),
+        ]));
+        value.rename_keys(to_snake_case);
+        assert_eq!(
+            value,
+            JsonValue::Object(Object::from_iter([
+                ("user_id".to_string(), JsonValue::Number(1.into())),
+                (
+                    "recent_orders".to_string(),
+                    JsonValue::Array(vec![JsonValue::Object(Object::from_iter([
+                        ("order_id".to_string(), JsonValue::Number(2.into())),
+                        ("total".to_string(), JsonValue::Number(3.into())),
+                    ]))]),
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn sorted() {
+        let json_str = r#"{"b":[{"z":1,"y":2}],"a":{"d":3,"c":4},"b":5}"#;
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Keep,
+            ..ParseOptions::default()
+        };
+        let value = Parser::with_options(Tokenizer::new(json_str.chars()), options)
+            .parse()
+            .unwrap();
+        let sorted = value.sorted();
+        #[cfg(not(feature = "indexmap"))]
+        assert_eq!(
+            sorted.to_string(),
+            r#"{"a":{"c":4,"d":3},"b":[{"y":2,"z":1}],"b":5}"#
+        );
+        // The repeated key replaces the first value.
+        #[cfg(feature = "indexmap")]
+        assert_eq!(sorted.to_string(), r#"{"a":{"c":4,"d":3},"b":5}"#);
+        #[cfg(not(feature = "indexmap"))]
+        assert_eq!(value.to_string(), json_str);
+
+        let mut value = value;
+        value.sort_keys();
+        assert_eq!(value, sorted);
+    }
+
+    #[test]
+    fn trim_keys() {
+        let mut value = JsonValue::Object(Object::from_iter([
+            (" a ".to_string(), JsonValue::Number(1.into())),
+            (
+                "nested\t".to_string(),
+                JsonValue::Array(vec![JsonValue::Object(Object::from_iter([(
+                    "\n b".to_string(),
+                    JsonValue::Null,
+                )]))]),
+            ),
+            ("a".to_string(), JsonValue::Number(2.into())),
+        ]));
+        assert_eq!(value.get_typed::<i64>("a"), Some(2));
+
+        value.trim_keys();
+        assert_eq!(value.get_typed::<i64>("a"), Some(1));
+        assert_eq!(
+            value,
+            JsonValue::Object(Object::from_iter([
+                ("a".to_string(), JsonValue::Number(1.into())),
+                (
+                    "nested".to_string(),
+                    JsonValue::Array(vec![JsonValue::Object(Object::from_iter([(
+                        "b".to_string(),
+                        JsonValue::Null,
+                    )]))]),
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn coerce_at_pointer() {
+        let json_str = r#"{"user":{"id":42,"zip":"01234","admin":"true"},"a/b":[1,false]}"#;
+        let mut value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        value
+            .coerce_at_pointer("/user/id", JsonType::String)
+            .unwrap();
+        value
+            .coerce_at_pointer("/user/admin", JsonType::Boolean)
+            .unwrap();
+        value
+            .coerce_at_pointer("/a~1b/1", JsonType::String)
+            .unwrap();
+        value
+            .coerce_at_pointer("/a~1b/0", JsonType::Number)
+            .unwrap();
+        assert_eq!(
+            value.to_string(),
+            r#"{"user":{"id":"42","zip":"01234","admin":true},"a/b":[1,"false"]}"#
+        );
+
+        value
+            .coerce_at_pointer("/user/id", JsonType::Number)
+            .unwrap();
+        assert_eq!(
+            value.pointer_mut("/user/id"),
+            Some(&mut JsonValue::Number(42.into()))
+        );
+
+        assert_eq!(
+            value.coerce_at_pointer("/user/email", JsonType::String),
+            Err(ParseError::PointerNotFound {
+                pointer: "/user/email".to_string()
+            })
+        );
+        let error = value
+            .coerce_at_pointer("/user", JsonType::String)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::InvalidConversion {
+                pointer: "/user".to_string(),
+                from: JsonType::Object,
+                to: JsonType::String
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "cannot convert object at /user to string"
+        );
+        assert!(value
+            .coerce_at_pointer("/user/zip", JsonType::Boolean)
+            .is_err());
+
+        let mut value = Parser::new(Tokenizer::new(r#"[1.5, "-2.5e-3", " 1"]"#.chars()))
+            .parse()
+            .unwrap();
+        value.coerce_at_pointer("/0", JsonType::String).unwrap();
+        assert_eq!(
+            value.pointer("/0"),
+            Some(&JsonValue::String("1.5".to_string()))
+        );
+        value.coerce_at_pointer("/0", JsonType::Number).unwrap();
+        value.coerce_at_pointer("/1", JsonType::Number).unwrap();
+        assert_eq!(value.to_string(), r#"[1.5,-0.0025," 1"]"#);
+        assert!(value.coerce_at_pointer("/2", JsonType::Number).is_err());
+    }
+}
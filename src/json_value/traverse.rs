@@ -0,0 +1,318 @@
+//! Iterative traversal over nested JSON values.
+
+use crate::json_value::{JsonType, JsonValue};
+use std::collections::{BTreeMap, HashSet};
+
+/// Statistics about a document, see `JsonValue::stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    /// Number of values, containers included.
+    pub nodes: usize,
+    /// Deepest nesting level, a lone scalar being at depth 1.
+    pub max_depth: usize,
+    /// Number of string values, keys are not included.
+    pub strings: usize,
+    /// Total length of all string values in bytes.
+    pub string_bytes: usize,
+    /// Number of distinct object keys across the whole document.
+    pub unique_keys: usize,
+}
+
+// Pre-order, depth-first iterator over a value and everything nested in it. An explicit stack is
+// used so deeply nested documents cannot overflow the call stack.
+pub(crate) struct Descendants<'a> {
+    stack: Vec<&'a JsonValue>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a JsonValue;
+
+    fn next(&mut self) -> Option<&'a JsonValue> {
+        let value = self.stack.pop()?;
+        // Children are pushed in reverse so they are visited in document order.
+        match value {
+            JsonValue::Array(elements) => self.stack.extend(elements.iter().rev()),
+            JsonValue::Object(members) => self
+                .stack
+                .extend(members.iter().rev().map(|(_, member)| member)),
+            _ => {}
+        }
+        Some(value)
+    }
+}
+
+impl JsonValue {
+    pub(crate) fn descendants(&self) -> Descendants<'_> {
+        Descendants { stack: vec![self] }
+    }
+
+    /// Calls `f` on every scalar in the value, depth-first and in document order, e.g. to
+    /// rewrite all numbers. Arrays and objects are only walked through, never passed to `f`,
+    /// and containers `f` creates are not descended into.
+    pub fn map_leaves(&mut self, mut f: impl FnMut(&mut JsonValue)) {
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            match value {
+                JsonValue::Array(elements) => stack.extend(elements.iter_mut().rev()),
+                JsonValue::Object(members) => {
+                    stack.extend(members.iter_mut().rev().map(|(_, member)| member))
+                }
+                _ => f(value),
+            }
+        }
+    }
+
+    /// Returns the first value, in depth-first document order, for which `pred` holds. The
+    /// value itself is checked first.
+    pub fn find(&self, pred: impl Fn(&JsonValue) -> bool) -> Option<&JsonValue> {
+        self.descendants().find(|value| pred(value))
+    }
+
+    /// Collects every value of type `ty`, the value itself included, in depth-first document
+    /// order.
+    pub fn collect_by_type(&self, ty: JsonType) -> Vec<&JsonValue> {
+        self.descendants()
+            .filter(|value| value.json_type() == ty)
+            .collect()
+    }
+
+    /// Whether `needle` equals the value itself or anything nested in it. Values are compared
+    /// with `==`: numbers match if they have the same value however they were written, so `1`
+    /// matches `1.0` and `1e3` matches `1000`, and objects must have the same members in the
+    /// same order, or in any order with the `indexmap` feature.
+    pub fn deep_contains(&self, needle: &JsonValue) -> bool {
+        self.descendants().any(|value| value == needle)
+    }
+
+    /// Deepest nesting level, a scalar being at depth 1 and `{"a":{"b":1}}` at depth 3. Empty
+    /// containers count as one level.
+    pub fn depth(&self) -> usize {
+        let mut max_depth = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((value, depth)) = stack.pop() {
+            max_depth = max_depth.max(depth);
+            match value {
+                JsonValue::Array(elements) => {
+                    stack.extend(elements.iter().map(|element| (element, depth + 1)))
+                }
+                JsonValue::Object(members) => {
+                    stack.extend(members.iter().map(|(_, member)| (member, depth + 1)))
+                }
+                _ => {}
+            }
+        }
+        max_depth
+    }
+
+    /// Collects statistics about the document, e.g. to profile inputs.
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        let mut keys = HashSet::new();
+        let mut stack = vec![(self, 1)];
+        while let Some((value, depth)) = stack.pop() {
+            stats.nodes += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            match value {
+                JsonValue::String(string) => {
+                    stats.strings += 1;
+                    stats.string_bytes += string.len();
+                }
+                JsonValue::Array(elements) => {
+                    stack.extend(elements.iter().map(|element| (element, depth + 1)))
+                }
+                JsonValue::Object(members) => {
+                    for (key, member) in members {
+                        keys.insert(key.as_str());
+                        stack.push((member, depth + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        stats.unique_keys = keys.len();
+        stats
+    }
+
+    /// Counts in how many of `records` each top-level key occurs, e.g. to tell optional fields
+    /// from required ones. A duplicate key counts once per record and records that are not
+    /// objects are skipped.
+    pub fn count_keys(records: &[JsonValue]) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for record in records {
+            let JsonValue::Object(members) = record else {
+                continue;
+            };
+            let keys: HashSet<&str> = members.iter().map(|(key, _)| key.as_str()).collect();
+            for key in keys {
+                *counts.entry(key.to_string()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonType, JsonValue, Stats};
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn find() {
+        let json_str = r#"{"a":[1,50,{"b":150}],"c":200,"d":{"e":120}}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        let found =
+            value.find(|value| matches!(value, JsonValue::Number(n) if n.as_i64() > Some(100)));
+        assert_eq!(found, Some(&JsonValue::Number(150.into())));
+
+        let found =
+            value.find(|value| matches!(value, JsonValue::Number(n) if n.as_i64() > Some(1000)));
+        assert_eq!(found, None);
+
+        let found = value.find(|value| matches!(value, JsonValue::Object(_)));
+        assert_eq!(found, Some(&value));
+    }
+
+    #[test]
+    fn map_leaves() {
+        let json_str = r#"{"a":[1,{"b":-2}],"c":"3","d":null}"#;
+        let mut value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        value.map_leaves(|leaf| {
+            if let JsonValue::Number(number) = leaf {
+                *leaf = JsonValue::String(number.to_string());
+            }
+        });
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":["1",{"b":"-2"}],"c":"3","d":null}"#
+        );
+
+        let mut visited = vec![];
+        value.map_leaves(|leaf| visited.push(leaf.to_string()));
+        assert_eq!(visited, [r#""1""#, r#""-2""#, r#""3""#, "null"]);
+    }
+
+    #[test]
+    fn collect_by_type() {
+        let json_str = r#"{"a":"x","b":[1,"y",{"c":"z","d":null}],"a2":{}}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        let strings: Vec<String> = value
+            .collect_by_type(JsonType::String)
+            .into_iter()
+            .map(JsonValue::to_string)
+            .collect();
+        assert_eq!(strings, [r#""x""#, r#""y""#, r#""z""#]);
+
+        let objects = value.collect_by_type(JsonType::Object);
+        assert_eq!(objects.len(), 3);
+        assert_eq!(objects[0], &value);
+        assert_eq!(
+            value.collect_by_type(JsonType::Boolean),
+            Vec::<&JsonValue>::new()
+        );
+    }
+
+    #[test]
+    fn deep_contains() {
+        let json_str = r#"{"a":[1,{"b":"x","c":[true,null]}],"d":2}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        assert!(value.deep_contains(&JsonValue::String("x".to_string())));
+        assert!(value.deep_contains(&JsonValue::Null));
+        assert!(!value.deep_contains(&JsonValue::Number(3.into())));
+        // Keys are not values.
+        assert!(!value.deep_contains(&JsonValue::String("b".to_string())));
+
+        let subtree = Parser::new(Tokenizer::new(r#"{"b":"x","c":[true,null]}"#.chars()))
+            .parse()
+            .unwrap();
+        assert!(value.deep_contains(&subtree));
+        assert!(value.deep_contains(&value));
+        let reordered = Parser::new(Tokenizer::new(r#"{"c":[true,null],"b":"x"}"#.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(value.deep_contains(&reordered), cfg!(feature = "indexmap"));
+
+        // Numbers are matched by value.
+        let floats = Parser::new(Tokenizer::new("[1.0, 1e3]".chars()))
+            .parse()
+            .unwrap();
+        assert!(floats.deep_contains(&JsonValue::Number(1.into())));
+        assert!(floats.deep_contains(&JsonValue::Number(1000.into())));
+        assert!(!floats.deep_contains(&JsonValue::Number(2.into())));
+    }
+
+    #[test]
+    fn depth() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse().unwrap();
+        assert_eq!(parse("1").depth(), 1);
+        assert_eq!(parse("{}").depth(), 1);
+        assert_eq!(parse(r#"{"a":1,"b":"x","c":null}"#).depth(), 2);
+        assert_eq!(parse(r#"{"a":{"b":1}}"#).depth(), 3);
+        assert_eq!(parse(r#"[1,[2,[3,[4]]],5]"#).depth(), 5);
+
+        let mut value = JsonValue::Null;
+        for _ in 0..10_000 {
+            value = JsonValue::Array(vec![value]);
+        }
+        assert_eq!(value.depth(), 10_001);
+    }
+
+    #[test]
+    fn stats() {
+        let json_str = r#"{"name":"hdjson","tags":["json","rust"],"meta":{"name":"é","stars":5}}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value.stats(),
+            Stats {
+                nodes: 8,
+                max_depth: 3,
+                strings: 4,
+                string_bytes: 16,
+                unique_keys: 4,
+            }
+        );
+
+        assert_eq!(
+            JsonValue::Null.stats(),
+            Stats {
+                nodes: 1,
+                max_depth: 1,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn count_keys() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse().unwrap();
+        let records = [
+            parse(r#"{"id":1,"name":"a","email":"a@example.com"}"#),
+            parse(r#"{"id":2,"name":"b","id":3}"#),
+            parse(r#"{"id":4,"phone":"555","nested":{"email":"x"}}"#),
+            parse("[1]"),
+        ];
+        let counts = JsonValue::count_keys(&records);
+        let counts: Vec<(&str, usize)> = counts.iter().map(|(k, c)| (k.as_str(), *c)).collect();
+        assert_eq!(
+            counts,
+            [
+                ("email", 1),
+                ("id", 3),
+                ("name", 2),
+                ("nested", 1),
+                ("phone", 1)
+            ]
+        );
+        assert!(JsonValue::count_keys(&[]).is_empty());
+    }
+}
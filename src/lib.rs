@@ -1,2 +1,24 @@
 // Declare modules
-mod tokenizer;
+mod error;
+mod json_value;
+mod parser;
+mod serializer;
+pub mod tokenizer;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use crate::error::ParseError;
+pub use crate::json_value::{Array, FromJson, JsonType, JsonValue, Number, Object, Stats};
+pub use crate::parser::{
+    parse_framed, parse_jsonc, parse_many, parse_prefix, parse_with_schema, Comments,
+    DuplicateKeys, NumberType, ParseOptions, Parser, StreamError, StreamParser,
+};
+pub use crate::serializer::{SerializeOptions, Serializer};
+#[cfg(feature = "yaml")]
+pub use crate::yaml::YamlError;
+
+/// Parses a JSON document. Both malformed tokens and misplaced ones are reported as a
+/// `ParseError` carrying the line and column where parsing stopped.
+pub fn parse(input: &str) -> Result<JsonValue, ParseError> {
+    Parser::new(tokenizer::Tokenizer::new(input.chars())).parse()
+}
@@ -0,0 +1,1322 @@
+//! Module for building JSON values out of tokens.
+
+mod stream;
+
+use crate::error::ParseError;
+use crate::json_value::number::parse_integer;
+use crate::json_value::{JsonValue, Number};
+pub use crate::parser::stream::{StreamError, StreamParser};
+use crate::tokenizer::{Token, TokenType, Tokenizer, TokenizerOptions};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::mem;
+use std::str::Chars;
+
+/// Comments collected in JSONC mode, keyed by the JSON Pointer of the value they precede.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comments {
+    leading: Vec<(String, Vec<String>)>,
+    // Position of each pointer in `leading`, which keeps the document order.
+    index: HashMap<String, usize>,
+}
+
+impl Comments {
+    pub fn new() -> Comments {
+        Comments::default()
+    }
+
+    /// Returns the comments in front of the value at `pointer`, e.g. `""` for the root or
+    /// `"/server/port"` for a nested member.
+    pub fn get(&self, pointer: &str) -> Option<&[String]> {
+        self.index
+            .get(pointer)
+            .map(|&i| self.leading[i].1.as_slice())
+    }
+
+    /// Attaches a comment in front of the value at `pointer`. The comment must include its
+    /// `//` or `/* */` delimiters.
+    pub fn push(&mut self, pointer: &str, comment: String) {
+        match self.index.get(pointer) {
+            Some(&i) => self.leading[i].1.push(comment),
+            None => {
+                self.index.insert(pointer.to_string(), self.leading.len());
+                self.leading.push((pointer.to_string(), vec![comment]));
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty()
+    }
+}
+
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DuplicateKeys {
+    /// Store every occurrence in the object, in document order. Lookups return the first one.
+    Keep,
+    /// Store only the first occurrence and drop the others.
+    KeepFirst,
+    /// Store only the last occurrence, at the position of the first one, like most JSON
+    /// parsers do.
+    #[default]
+    KeepLast,
+    /// Fail with `ParseError::DuplicateKey` at the second occurrence.
+    Reject,
+}
+
+/// Kind of number expected at a path, see `parse_with_schema`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberType {
+    /// Only integers are accepted.
+    I64,
+    /// Integers and numbers with a fraction or exponent are accepted, integers are converted to
+    /// floats.
+    F64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub duplicate_keys: DuplicateKeys,
+    /// Number types expected at the given JSON Pointers. Paths that are not listed accept any
+    /// number, and values at listed paths that are not numbers are not checked.
+    pub number_types: Vec<(String, NumberType)>,
+    /// Keep strings and keys exactly as written, e.g. `\u0041` stays six characters instead of
+    /// becoming `A`. Serialize such values with `SerializeOptions::keep_escapes`.
+    pub keep_escapes: bool,
+    /// Store numbers as written and only convert them when they are read, see `Number`. Saves
+    /// work on documents where most numbers are never looked at.
+    pub lazy_numbers: bool,
+    /// How many arrays and objects may be nested inside each other. Deeper documents fail with
+    /// `ParseError::TooDeep` instead of overflowing the stack.
+    pub max_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            duplicate_keys: DuplicateKeys::default(),
+            number_types: vec![],
+            keep_escapes: false,
+            lazy_numbers: false,
+            max_depth: 128,
+        }
+    }
+}
+
+pub struct Parser<'a> {
+    tokenizer: Tokenizer<'a>,
+    options: ParseOptions,
+    peeked: Option<Token<'a>>,
+    // JSON Pointer to the value currently being parsed.
+    path: String,
+    // Number of arrays and objects currently open.
+    depth: usize,
+    comments: Comments,
+    pending_comments: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokenizer: Tokenizer<'a>) -> Parser<'a> {
+        Parser::with_options(tokenizer, ParseOptions::default())
+    }
+
+    pub fn with_options(tokenizer: Tokenizer<'a>, options: ParseOptions) -> Parser<'a> {
+        Parser {
+            tokenizer,
+            options,
+            peeked: None,
+            path: String::new(),
+            depth: 0,
+            comments: Comments::new(),
+            pending_comments: vec![],
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+        let value = self.parse_next()?;
+        if let Some(token) = self.next_token()? {
+            return Err(unexpected(token));
+        }
+        Ok(value)
+    }
+
+    // Parses a single value, leaving whatever follows it untouched.
+    pub(crate) fn parse_next(&mut self) -> Result<JsonValue, ParseError> {
+        // The path is not unwound on errors, so it still points at the failing value.
+        self.parse_value().map_err(|error| {
+            if self.path.is_empty() {
+                error
+            } else {
+                ParseError::Context {
+                    path: mem::take(&mut self.path),
+                    source: Box::new(error),
+                }
+            }
+        })
+    }
+
+    // Whether only whitespace and comments are left.
+    pub(crate) fn is_finished(&mut self) -> Result<bool, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_token()?;
+        }
+        Ok(self.peeked.is_none())
+    }
+
+    // The part of the input the tokenizer has not consumed yet.
+    pub(crate) fn remaining(&self) -> &'a str {
+        self.tokenizer.remaining()
+    }
+
+    /// Comments collected so far. Only JSONC tokenizers produce any.
+    pub fn comments(&self) -> &Comments {
+        &self.comments
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, ParseError> {
+        if let Some(peeked) = self.peeked.take() {
+            return Ok(Some(peeked));
+        }
+
+        while let Some(token) = self.tokenizer.next_token() {
+            if let TokenType::Comment(comment) = token.token_type {
+                self.pending_comments.push(comment);
+                continue;
+            }
+            return Ok(Some(token));
+        }
+        match self.tokenizer.error() {
+            Some(error) => Err(error.clone()),
+            None => Ok(None),
+        }
+    }
+
+    fn next_is(&mut self, token_type: &TokenType<'_>) -> Result<bool, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_token()?;
+        }
+        Ok(matches!(&self.peeked, Some(token) if token.token_type == *token_type))
+    }
+
+    fn expect_token(&mut self) -> Result<Token<'a>, ParseError> {
+        match self.next_token()? {
+            Some(next) => Ok(next),
+            None => Err(ParseError::UnexpectedEof {
+                line: self.tokenizer.line(),
+                col: self.tokenizer.column(),
+            }),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
+        let token = self.expect_token()?;
+        self.attach_comments();
+        let (line, col) = (token.line, token.position);
+        match token.token_type {
+            TokenType::ObjectStart | TokenType::ArrayStart => {
+                if self.depth == self.options.max_depth {
+                    return Err(ParseError::TooDeep {
+                        limit: self.options.max_depth,
+                        line,
+                        col,
+                    });
+                }
+                self.depth += 1;
+                let container = if token.token_type == TokenType::ObjectStart {
+                    self.parse_object()
+                } else {
+                    self.parse_array()
+                };
+                self.depth -= 1;
+                container
+            }
+            // Integers where the schema expects floats are stored as floats, e.g. `1` as `1.0`.
+            TokenType::Integer(number) if self.number_type() == Some(NumberType::F64) => {
+                match number.parse().ok().and_then(Number::from_f64) {
+                    Some(float) => Ok(JsonValue::Number(float)),
+                    None => Ok(JsonValue::Number(Number::lazy(number))),
+                }
+            }
+            // A number with an exponent but no fraction, such as `1e3`, is still an integer, while
+            // integers too large for an i64 are not.
+            TokenType::Integer(number) | TokenType::Float(number)
+                if self.number_type() == Some(NumberType::I64) =>
+            {
+                parse_integer(&number)
+                    .map(|number| JsonValue::Number(number.into()))
+                    .ok_or(ParseError::NumberTypeMismatch {
+                        expected: NumberType::I64,
+                        line,
+                        col,
+                    })
+            }
+            // Converted only when read, see `Number`.
+            TokenType::Integer(number) | TokenType::Float(number) if self.options.lazy_numbers => {
+                Ok(JsonValue::Number(Number::lazy(number)))
+            }
+            // Numbers too large for an i64 or f64 are kept as written, so no precision is lost.
+            TokenType::Integer(number) => match parse_integer(&number) {
+                Some(integer) => Ok(JsonValue::Number(integer.into())),
+                None => Ok(JsonValue::Number(Number::lazy(number))),
+            },
+            TokenType::Float(number) => match number.parse().ok().and_then(Number::from_f64) {
+                Some(float) => Ok(JsonValue::Number(float)),
+                None => Ok(JsonValue::Number(Number::lazy(number))),
+            },
+            TokenType::String(raw) => match self.decode_string(&raw) {
+                Some(string) => Ok(JsonValue::String(string)),
+                None => Err(ParseError::InvalidEscape {
+                    found: raw.into_owned(),
+                    line,
+                    col,
+                }),
+            },
+            TokenType::Boolean(boolean) => Ok(JsonValue::Boolean(boolean)),
+            TokenType::Null => Ok(JsonValue::Null),
+            token_type => Err(unexpected(Token::new(token_type, line, col))),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, ParseError> {
+        let mut elements = vec![];
+        if self.next_is(&TokenType::ArrayEnd)? {
+            self.next_token()?;
+        } else {
+            loop {
+                let path_len = self.path.len();
+                self.path.push('/');
+                self.path.push_str(&elements.len().to_string());
+                elements.push(self.parse_value()?);
+                self.path.truncate(path_len);
+
+                let token = self.expect_token()?;
+                match token.token_type {
+                    TokenType::Comma => continue,
+                    TokenType::ArrayEnd => break,
+                    _ => return Err(unexpected(token)),
+                }
+            }
+        }
+
+        // Comments that are not followed by a value inside the array are dropped.
+        self.pending_comments.clear();
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, ParseError> {
+        let mut members: Vec<(String, JsonValue)> = vec![];
+        // Position of each key in `members`, so duplicates are found without a scan.
+        let mut index: HashMap<String, usize> = HashMap::new();
+        if self.next_is(&TokenType::ObjectEnd)? {
+            self.next_token()?;
+        } else {
+            loop {
+                let token = self.expect_token()?;
+                let key = match token.token_type {
+                    TokenType::String(ref raw) => match self.decode_string(raw) {
+                        Some(key) => key,
+                        None => {
+                            return Err(ParseError::InvalidEscape {
+                                found: raw.to_string(),
+                                line: token.line,
+                                col: token.position,
+                            })
+                        }
+                    },
+                    _ => return Err(unexpected(token)),
+                };
+                // Applied to every object on its own, however deeply nested.
+                let duplicate = match self.options.duplicate_keys {
+                    DuplicateKeys::Keep => None,
+                    _ => index.get(&key).copied(),
+                };
+                if duplicate.is_some() && self.options.duplicate_keys == DuplicateKeys::Reject {
+                    return Err(ParseError::DuplicateKey {
+                        key,
+                        line: token.line,
+                        col: token.position,
+                    });
+                }
+
+                let token = self.expect_token()?;
+                if token.token_type != TokenType::Colon {
+                    return Err(unexpected(token));
+                }
+
+                let path_len = self.path.len();
+                self.path.push('/');
+                self.path.push_str(&escape_pointer_token(&key));
+                let member = self.parse_value()?;
+                self.path.truncate(path_len);
+                match (duplicate, self.options.duplicate_keys) {
+                    (Some(i), DuplicateKeys::KeepLast) => members[i].1 = member,
+                    (Some(_), _) => {}
+                    (None, DuplicateKeys::Keep) => members.push((key, member)),
+                    (None, _) => {
+                        index.insert(key.clone(), members.len());
+                        members.push((key, member));
+                    }
+                }
+
+                let token = self.expect_token()?;
+                match token.token_type {
+                    TokenType::Comma => continue,
+                    TokenType::ObjectEnd => break,
+                    _ => return Err(unexpected(token)),
+                }
+            }
+        }
+
+        // Comments that are not followed by a member inside the object are dropped.
+        self.pending_comments.clear();
+        // Collecting only changes the members with the `indexmap` feature.
+        Ok(JsonValue::Object(members.into_iter().collect()))
+    }
+
+    fn decode_string(&self, raw: &str) -> Option<String> {
+        if self.options.keep_escapes {
+            Some(raw.to_string())
+        } else {
+            decode_string(raw).map(Cow::into_owned)
+        }
+    }
+
+    // The number type the schema expects at the current path.
+    fn number_type(&self) -> Option<NumberType> {
+        self.options
+            .number_types
+            .iter()
+            .find(|(path, _)| *path == self.path)
+            .map(|(_, number_type)| *number_type)
+    }
+
+    fn attach_comments(&mut self) {
+        for comment in mem::take(&mut self.pending_comments) {
+            self.comments.push(&self.path, comment);
+        }
+    }
+}
+
+/// Parses a JSONC document, returning the value together with the comments found in front of
+/// each value. Comments that do not precede a value, e.g. right before a closing bracket, are
+/// discarded.
+pub fn parse_jsonc(input: &str) -> Result<(JsonValue, Comments), ParseError> {
+    let options = TokenizerOptions {
+        jsonc: true,
+        ..TokenizerOptions::default()
+    };
+    let mut parser = Parser::new(Tokenizer::with_options(input.chars(), options));
+    let value = parser.parse()?;
+    Ok((value, parser.comments))
+}
+
+/// Parses `input`, checking the numbers at the JSON Pointers listed in `schema` against the
+/// type expected there, e.g. `[("/id", NumberType::I64)]` rejects `{"id":1.5}`.
+pub fn parse_with_schema(
+    input: &str,
+    schema: &[(&str, NumberType)],
+) -> Result<JsonValue, ParseError> {
+    let options = ParseOptions {
+        number_types: schema
+            .iter()
+            .map(|(path, number_type)| (path.to_string(), *number_type))
+            .collect(),
+        ..ParseOptions::default()
+    };
+    Parser::with_options(Tokenizer::new(input.chars()), options).parse()
+}
+
+/// Parses a sequence of whitespace separated values, such as JSON Lines.
+pub fn parse_many(input: &str) -> Result<Vec<JsonValue>, ParseError> {
+    let mut parser = Parser::new(Tokenizer::new(input.chars()));
+    let mut values = vec![];
+    while !parser.is_finished()? {
+        values.push(parser.parse_next()?);
+    }
+    Ok(values)
+}
+
+/// Parses the first value in `input` and returns it together with everything after it, e.g.
+/// for inputs where JSON is followed by other data. The rest is returned as-is, including any
+/// whitespace directly after the value.
+pub fn parse_prefix(input: &str) -> Result<(JsonValue, &str), ParseError> {
+    let mut parser = Parser::new(Tokenizer::new(input.chars()));
+    let value = parser.parse_next()?;
+    Ok((value, parser.remaining()))
+}
+
+/// Parses a frame made of a 4-byte big-endian length followed by that many bytes of JSON, as
+/// used by some binary protocols. Returns the value and the size of the whole frame, so the
+/// next frame starts at that offset.
+pub fn parse_framed(bytes: &[u8]) -> Result<(JsonValue, usize), ParseError> {
+    let truncated = ParseError::TruncatedFrame {
+        expected: 4,
+        available: bytes.len(),
+    };
+    let prefix: [u8; 4] = bytes.get(..4).ok_or(truncated)?.try_into().unwrap();
+    let len = u32::from_be_bytes(prefix) as usize;
+    let Some(payload) = bytes[4..].get(..len) else {
+        // Saturates on 32-bit targets, where a length near u32::MAX plus the prefix overflows.
+        return Err(ParseError::TruncatedFrame {
+            expected: len.saturating_add(4),
+            available: bytes.len(),
+        });
+    };
+
+    let input = std::str::from_utf8(payload).map_err(|error| ParseError::InvalidUtf8 {
+        offset: 4 + error.valid_up_to(),
+    })?;
+    let value = Parser::new(Tokenizer::new(input.chars())).parse()?;
+    Ok((value, len + 4))
+}
+
+// Escapes a key for use as a JSON Pointer reference token, as defined by RFC-6901.
+pub(crate) fn escape_pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unexpected(token: Token) -> ParseError {
+    ParseError::UnexpectedToken {
+        found: token.token_type.into_owned(),
+        line: token.line,
+        col: token.position,
+    }
+}
+
+// Translates the escape sequences that the tokenizer keeps verbatim. Strings without any are
+// returned as they are.
+pub(crate) fn decode_string(raw: &str) -> Option<Cow<'_, str>> {
+    if !raw.contains('\\') {
+        return Some(Cow::Borrowed(raw));
+    }
+
+    let mut decoded = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+
+        let unescaped = match chars.next()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => match read_code_unit(&mut chars)? {
+                high @ 0xd800..=0xdbff => {
+                    if chars.next()? != '\\' || chars.next()? != 'u' {
+                        return None;
+                    }
+                    let low = read_code_unit(&mut chars)?;
+                    if !(0xdc00..=0xdfff).contains(&low) {
+                        return None;
+                    }
+                    char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))?
+                }
+                code_unit => char::from_u32(code_unit)?,
+            },
+            _ => return None,
+        };
+        decoded.push(unescaped);
+    }
+    Some(Cow::Owned(decoded))
+}
+
+// Reads the four hex digits of a `\u` escape. Surrogate pairs are combined by the caller.
+fn read_code_unit(chars: &mut Chars) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).collect();
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ParseError;
+    use crate::json_value::{JsonValue, Number, Object};
+    use crate::parser::{
+        parse_framed, parse_jsonc, parse_many, parse_prefix, parse_with_schema, DuplicateKeys,
+        NumberType, ParseOptions, Parser,
+    };
+    use crate::serializer::SerializeOptions;
+    use crate::tokenizer::{TokenType, Tokenizer, TokenizerOptions};
+
+    #[test]
+    fn nested_values() {
+        let json_str = r#"{"a":[1,2,{"b":true}]}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(Object::from_iter([(
+                "a".to_string(),
+                JsonValue::Array(vec![
+                    JsonValue::Number(1.into()),
+                    JsonValue::Number(2.into()),
+                    JsonValue::Object(Object::from_iter([(
+                        "b".to_string(),
+                        JsonValue::Boolean(true)
+                    )])),
+                ])
+            )]))
+        );
+        assert_eq!(value.to_string(), json_str);
+
+        let json_str = r#"[null, "x", {}, [], [[-3]]]"#;
+        assert_eq!(
+            Parser::new(Tokenizer::new(json_str.chars())).parse(),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Null,
+                JsonValue::String("x".to_string()),
+                JsonValue::Object(Object::new()),
+                JsonValue::Array(vec![]),
+                JsonValue::Array(vec![JsonValue::Array(vec![JsonValue::Number((-3).into())])]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn jsonc_comments() {
+        let config = r#"// Server configuration
+{
+    // Address to bind to
+    "host": "localhost",
+    /* Must be above 1024 */
+    "port": 8080,
+    "paths": [
+        // Served first
+        "/static",
+        "/assets"
+    ]
+}"#;
+        let (value, comments) = parse_jsonc(config).unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(Object::from_iter([
+                (
+                    "host".to_string(),
+                    JsonValue::String("localhost".to_string())
+                ),
+                ("port".to_string(), JsonValue::Number(8080.into())),
+                (
+                    "paths".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::String("/static".to_string()),
+                        JsonValue::String("/assets".to_string()),
+                    ])
+                ),
+            ]))
+        );
+        assert_eq!(
+            comments.get(""),
+            Some(&["// Server configuration".to_string()][..])
+        );
+        assert_eq!(
+            comments.get("/port"),
+            Some(&["/* Must be above 1024 */".to_string()][..])
+        );
+        assert_eq!(
+            comments.get("/paths/0"),
+            Some(&["// Served first".to_string()][..])
+        );
+        assert_eq!(comments.get("/paths/1"), None);
+
+        let serialized = value.to_string_with_comments(&comments, &SerializeOptions::default());
+        assert_eq!(
+            serialized,
+            "// Server configuration\n{// Address to bind to\n\"host\":\"localhost\",\
+             /* Must be above 1024 */\"port\":8080,\"paths\":[// Served first\n\"/static\",\
+             \"/assets\"]}"
+        );
+
+        // The serialized output keeps every comment in place.
+        let (reparsed, recomments) = parse_jsonc(&serialized).unwrap();
+        assert_eq!(reparsed, value);
+        assert_eq!(recomments, comments);
+    }
+
+    #[test]
+    fn error_path() {
+        let json_str = r#"{"items":[{"price":1},{"price":2},{"price":3},{"price":1.}]}"#;
+        let mut parser = Parser::new(Tokenizer::new(json_str.chars()));
+        let error = parser.parse().unwrap_err();
+        assert_eq!(error.path(), Some("/items/3/price"));
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::InvalidNumber { line: 1, col: 57 }
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid number at line 1, column 57 in /items/3/price"
+        );
+
+        // Errors at the top level carry no path.
+        let mut parser = Parser::new(Tokenizer::new("[1] 2".chars()));
+        assert_eq!(parser.parse().unwrap_err().path(), None);
+    }
+
+    #[test]
+    fn integer_exponents() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert_eq!(parse("1e3"), Ok(JsonValue::Number(1000.into())));
+        assert_eq!(parse("-2e3"), Ok(JsonValue::Number((-2000).into())));
+        assert_eq!(parse("5E+2"), Ok(JsonValue::Number(500.into())));
+        assert_eq!(parse("500e-2"), Ok(JsonValue::Number(5.into())));
+        assert_eq!(
+            parse("1e18"),
+            Ok(JsonValue::Number(1_000_000_000_000_000_000.into()))
+        );
+    }
+
+    #[test]
+    fn arbitrary_precision() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        let json_str = "[1234567890123456789012345678901234567890,12345678901234567890,1e400]";
+        let value = parse(json_str).unwrap();
+        assert_eq!(value.to_string(), json_str);
+        let JsonValue::Array(elements) = &value else {
+            panic!("expected an array");
+        };
+        let numbers: Vec<&Number> = elements
+            .iter()
+            .map(|element| match element {
+                JsonValue::Number(number) => number,
+                _ => panic!("expected a number"),
+            })
+            .collect();
+
+        assert!(numbers[0].is_integer());
+        assert_eq!((numbers[0].as_i64(), numbers[0].as_u64()), (None, None));
+        assert_eq!(numbers[0].as_f64(), Some(1.2345678901234568e39));
+        assert_eq!(numbers[1].as_i64(), None);
+        assert_eq!(numbers[1].as_u64(), Some(12345678901234567890));
+        assert!(numbers[2].is_float());
+        assert_eq!(numbers[2].as_f64(), None);
+
+        // Compared by their text, as no conversion is exact.
+        assert_eq!(parse(json_str), Ok(value));
+        assert_ne!(
+            parse("1234567890123456789012345678901234567890"),
+            parse("1234567890123456789012345678901234567891")
+        );
+    }
+
+    #[test]
+    fn floats() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        let value = parse("[1, 1.5, 1e3, -2.5e-3]").unwrap();
+        let JsonValue::Array(elements) = &value else {
+            panic!("expected an array");
+        };
+        let numbers: Vec<&Number> = elements
+            .iter()
+            .map(|element| match element {
+                JsonValue::Number(number) => number,
+                _ => panic!("expected a number"),
+            })
+            .collect();
+        assert!(numbers[0].is_integer());
+        assert!(numbers[1].is_float() && numbers[2].is_float() && numbers[3].is_float());
+        assert_eq!(numbers[1].as_f64(), Some(1.5));
+        assert_eq!(numbers[2].as_i64(), Some(1000));
+        assert_eq!(numbers[3].as_f64(), Some(-0.0025));
+        assert_eq!(value.to_string(), "[1,1.5,1000.0,-0.0025]");
+        assert_eq!(parse("1e19").unwrap().as_f64_coerced(), Some(1e19));
+    }
+
+    #[test]
+    fn heterogeneous_arrays() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert_eq!(
+            parse(r#"[1, "a", true, null, {}, []]"#),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Number(1.into()),
+                JsonValue::String("a".to_string()),
+                JsonValue::Boolean(true),
+                JsonValue::Null,
+                JsonValue::Object(Object::new()),
+                JsonValue::Array(vec![]),
+            ]))
+        );
+        assert_eq!(
+            parse(r#"[[], {"a": [null]}, false, -3]"#),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Array(vec![]),
+                JsonValue::Object(Object::from_iter([(
+                    "a".to_string(),
+                    JsonValue::Array(vec![JsonValue::Null])
+                )])),
+                JsonValue::Boolean(false),
+                JsonValue::Number((-3).into()),
+            ]))
+        );
+
+        // Every element must be separated by exactly one comma.
+        assert!(parse("[1 2]").is_err());
+        assert!(parse("[1, {}]").is_ok());
+        assert!(parse("[{} []]").is_err());
+        assert!(parse("[1,]").is_err());
+    }
+
+    #[test]
+    fn stray_colons() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        let error = parse(":").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::UnexpectedToken {
+                found: TokenType::Colon,
+                line: 1,
+                col: 1
+            }
+        );
+        assert_eq!(error.to_string(), "unexpected ':' at line 1, column 1");
+
+        let error = parse("[1:2]").unwrap_err();
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::UnexpectedToken {
+                found: TokenType::Colon,
+                line: 1,
+                col: 3
+            }
+        );
+
+        // Colons are only valid between a key and its value.
+        assert!(parse(r#"{"a"::1}"#).is_err());
+        assert!(parse(r#"{:1}"#).is_err());
+        assert!(parse(r#"{"a":1:}"#).is_err());
+        assert!(parse(r#"1:"#).is_err());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let json_str = r#"{"a":1,"b":2,"a":3}"#;
+        let parse = |duplicate_keys| {
+            let options = ParseOptions {
+                duplicate_keys,
+                ..ParseOptions::default()
+            };
+            Parser::with_options(Tokenizer::new(json_str.chars()), options).parse()
+        };
+        let members = |members: &[(&str, i64)]| {
+            JsonValue::Object(
+                members
+                    .iter()
+                    .map(|(key, number)| (key.to_string(), JsonValue::Number((*number).into())))
+                    .collect(),
+            )
+        };
+
+        let value = Parser::new(Tokenizer::new(json_str.chars())).parse();
+        assert_eq!(value, Ok(members(&[("a", 3), ("b", 2)])));
+        assert_eq!(parse(DuplicateKeys::KeepLast), value);
+        assert_eq!(
+            parse(DuplicateKeys::KeepFirst),
+            Ok(members(&[("a", 1), ("b", 2)]))
+        );
+        assert_eq!(
+            parse(DuplicateKeys::Reject),
+            Err(ParseError::DuplicateKey {
+                key: "a".to_string(),
+                line: 1,
+                col: 14
+            })
+        );
+
+        let value = parse(DuplicateKeys::Keep).unwrap();
+        #[cfg(not(feature = "indexmap"))]
+        {
+            assert_eq!(value, members(&[("a", 1), ("b", 2), ("a", 3)]));
+            assert_eq!(value.get_typed::<i64>("a"), Some(1));
+        }
+        #[cfg(feature = "indexmap")]
+        {
+            assert_eq!(value, members(&[("a", 3), ("b", 2)]));
+            assert_eq!(value.to_string(), r#"{"a":3,"b":2}"#);
+        }
+    }
+
+    #[test]
+    fn nested_duplicate_keys() {
+        let parse = |input: &str, duplicate_keys| {
+            let options = ParseOptions {
+                duplicate_keys,
+                ..ParseOptions::default()
+            };
+            Parser::with_options(Tokenizer::new(input.chars()), options).parse()
+        };
+        let json_str = r#"{"a":{"b":1,"b":2},"list":[{"c":{"d":3,"d":4}}]}"#;
+        assert_eq!(
+            parse(json_str, DuplicateKeys::KeepLast)
+                .unwrap()
+                .to_string(),
+            r#"{"a":{"b":2},"list":[{"c":{"d":4}}]}"#
+        );
+        assert_eq!(
+            parse(json_str, DuplicateKeys::KeepFirst)
+                .unwrap()
+                .to_string(),
+            r#"{"a":{"b":1},"list":[{"c":{"d":3}}]}"#
+        );
+
+        let error = parse(json_str, DuplicateKeys::Reject).unwrap_err();
+        assert_eq!(error.path(), Some("/a"));
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::DuplicateKey {
+                key: "b".to_string(),
+                line: 1,
+                col: 13
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            r#"duplicate key "b" at line 1, column 13 in /a"#
+        );
+        let error = parse(r#"[{"c":{"d":3,"d":4}}]"#, DuplicateKeys::Reject).unwrap_err();
+        assert_eq!(error.path(), Some("/0/c"));
+    }
+
+    #[test]
+    fn schema() {
+        let schema = [
+            ("/id", NumberType::I64),
+            ("/items/0/price", NumberType::F64),
+        ];
+        let json_str = r#"{"id":7,"items":[{"price":3}]}"#;
+        let value = parse_with_schema(json_str, &schema).unwrap();
+        let JsonValue::Number(price) = &value["items"][0]["price"] else {
+            panic!("expected a number");
+        };
+        assert!(price.is_float());
+        assert_eq!(price.as_f64(), Some(3.0));
+        assert_eq!(value.to_string(), r#"{"id":7,"items":[{"price":3.0}]}"#);
+
+        let error = parse_with_schema(r#"{"id":1.0,"items":[]}"#, &schema).unwrap_err();
+        assert_eq!(error.path(), Some("/id"));
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::NumberTypeMismatch {
+                expected: NumberType::I64,
+                line: 1,
+                col: 7
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "expected an integer at line 1, column 7 in /id"
+        );
+
+        let error = parse_with_schema(r#"{"id":99999999999999999999}"#, &schema).unwrap_err();
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::NumberTypeMismatch {
+                expected: NumberType::I64,
+                line: 1,
+                col: 7
+            }
+        );
+
+        // Values that are not numbers are left to other checks.
+        assert!(parse_with_schema(r#"{"id":"seven"}"#, &schema).is_ok());
+    }
+
+    #[test]
+    fn many() {
+        assert_eq!(
+            parse_many("1 [2]\n{}\n"),
+            Ok(vec![
+                JsonValue::Number(1.into()),
+                JsonValue::Array(vec![JsonValue::Number(2.into())]),
+                JsonValue::Object(Object::new())
+            ])
+        );
+        assert_eq!(parse_many(" \n"), Ok(vec![]));
+        // Positions are counted across all values.
+        assert!(matches!(
+            parse_many("1\n[2,\n3"),
+            Err(ParseError::UnexpectedEof { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(
+            parse_prefix("{}rest"),
+            Ok((JsonValue::Object(Object::new()), "rest"))
+        );
+        assert_eq!(
+            parse_prefix("[1, 2] \n{}"),
+            Ok((
+                JsonValue::Array(vec![
+                    JsonValue::Number(1.into()),
+                    JsonValue::Number(2.into())
+                ]),
+                " \n{}"
+            ))
+        );
+        assert_eq!(
+            parse_prefix("12 rest"),
+            Ok((JsonValue::Number(12.into()), " rest"))
+        );
+        assert_eq!(parse_prefix("null"), Ok((JsonValue::Null, "")));
+        assert!(parse_prefix("[1,").is_err());
+    }
+
+    #[test]
+    fn framed() {
+        let mut frames = vec![0, 0, 0, 7];
+        frames.extend_from_slice(br#"[1,"a"]"#);
+        frames.extend_from_slice(&[0, 0, 0, 4]);
+        frames.extend_from_slice(b"null");
+        let (value, consumed) = parse_framed(&frames).unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Array(vec![
+                JsonValue::Number(1.into()),
+                JsonValue::String("a".to_string())
+            ])
+        );
+        assert_eq!(consumed, 11);
+        assert_eq!(parse_framed(&frames[consumed..]), Ok((JsonValue::Null, 8)));
+
+        assert_eq!(
+            parse_framed(&frames[..9]),
+            Err(ParseError::TruncatedFrame {
+                expected: 11,
+                available: 9
+            })
+        );
+        assert_eq!(
+            parse_framed(&[0xff, 0xff, 0xff, 0xff, b'1']),
+            Err(ParseError::TruncatedFrame {
+                expected: (u32::MAX as usize).saturating_add(4),
+                available: 5
+            })
+        );
+        assert_eq!(
+            parse_framed(&[0, 0]),
+            Err(ParseError::TruncatedFrame {
+                expected: 4,
+                available: 2
+            })
+        );
+        assert_eq!(
+            parse_framed(&[0, 0, 0, 2, b'1', 0xff]),
+            Err(ParseError::InvalidUtf8 { offset: 5 })
+        );
+    }
+
+    #[test]
+    fn keep_escapes() {
+        let json_str = r#"{"\u0041\n":"\u0041\/\u00e9"}"#;
+        let options = ParseOptions {
+            keep_escapes: true,
+            ..ParseOptions::default()
+        };
+        let value = Parser::with_options(Tokenizer::new(json_str.chars()), options)
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(Object::from_iter([(
+                r#"\u0041\n"#.to_string(),
+                JsonValue::String(r#"\u0041\/\u00e9"#.to_string())
+            )]))
+        );
+
+        let options = SerializeOptions {
+            keep_escapes: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(value.to_string_with(&options), json_str);
+    }
+
+    #[test]
+    fn trailing_content() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        let error = parse("42abc").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::UnexpectedChar {
+                ch: 'a',
+                line: 1,
+                col: 3
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "unexpected character 'a' at line 1, column 3"
+        );
+        assert_eq!(
+            parse("42 abc"),
+            Err(ParseError::UnexpectedChar {
+                ch: 'a',
+                line: 1,
+                col: 4
+            })
+        );
+        assert_eq!(
+            parse("42 nope"),
+            Err(ParseError::UnexpectedChar {
+                ch: 'n',
+                line: 1,
+                col: 4
+            })
+        );
+        assert_eq!(
+            parse("42true"),
+            Err(ParseError::UnexpectedToken {
+                found: TokenType::Boolean(true),
+                line: 1,
+                col: 3
+            })
+        );
+    }
+
+    #[test]
+    fn empty_elements() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        // The error points at the second comma, where a value was expected.
+        let error = parse("[1,,2]").unwrap_err();
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::UnexpectedToken {
+                found: TokenType::Comma,
+                line: 1,
+                col: 4
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "unexpected ',' at line 1, column 4 in /1"
+        );
+
+        let error = parse(r#"{"a":1,,"b":2}"#).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::UnexpectedToken {
+                found: TokenType::Comma,
+                line: 1,
+                col: 8
+            }
+        );
+        assert!(parse("[,]").is_err());
+        assert!(parse("[1,]").is_err());
+    }
+
+    #[test]
+    fn leading_commas() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        let error = parse("[,1]").unwrap_err();
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::UnexpectedToken {
+                found: TokenType::Comma,
+                line: 1,
+                col: 2
+            }
+        );
+
+        for input in ["{,}", r#"{,"a":1}"#] {
+            assert_eq!(
+                parse(input),
+                Err(ParseError::UnexpectedToken {
+                    found: TokenType::Comma,
+                    line: 1,
+                    col: 2
+                }),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert_eq!(
+            parse(r#"["\uD83D\uDCA9", "\ud83d\udE00!"]"#),
+            Ok(JsonValue::Array(vec![
+                JsonValue::String("\u{1f4a9}".to_string()),
+                JsonValue::String("\u{1f600}!".to_string()),
+            ]))
+        );
+        assert_eq!(
+            parse(r#""\uD83D""#),
+            Err(ParseError::InvalidEscape {
+                found: r"\uD83D".to_string(),
+                line: 1,
+                col: 2
+            })
+        );
+    }
+
+    #[test]
+    fn lazy_numbers() {
+        let options = ParseOptions {
+            lazy_numbers: true,
+            ..ParseOptions::default()
+        };
+        let parse = |input: &str| {
+            Parser::with_options(Tokenizer::new(input.chars()), options.clone()).parse()
+        };
+        let value = parse("[1, -2e3, 1e19]").unwrap();
+        let JsonValue::Array(elements) = &value else {
+            panic!("expected an array");
+        };
+        assert_eq!(elements[0], JsonValue::Number(1.into()));
+        assert_eq!(elements[1], JsonValue::Number((-2000).into()));
+        assert_eq!(elements[1].as_i64_coerced(), Some(-2000));
+        // Too large, but only noticed when read.
+        assert_eq!(elements[2].as_i64_coerced(), None);
+        assert_eq!(value.to_string(), "[1,-2e3,1e19]");
+
+        let JsonValue::Number(float) = parse("1.5").unwrap() else {
+            panic!("expected a number");
+        };
+        assert!(float.is_float());
+        assert_eq!(float.as_f64(), Some(1.5));
+
+        // Lazy numbers read back exactly like eagerly parsed ones.
+        for input in [
+            "2",
+            "2.0",
+            "1.5e3",
+            "-0.25",
+            "1e19",
+            "18446744073709551615",
+            "1e400",
+            "123456789012345678901234567890123456789012",
+        ] {
+            let eager = Parser::new(Tokenizer::new(input.chars())).parse().unwrap();
+            let lazy = parse(input).unwrap();
+            assert_eq!(lazy, eager, "{}", input);
+            assert_eq!(lazy.stable_hash(), eager.stable_hash(), "{}", input);
+            assert_eq!(lazy.as_i64_coerced(), eager.as_i64_coerced(), "{}", input);
+            assert_eq!(lazy.as_f64_coerced(), eager.as_f64_coerced(), "{}", input);
+        }
+        assert_eq!(parse("2.0").unwrap(), JsonValue::Number(2.into()));
+        assert_eq!(parse("1.5e3").unwrap().as_i64_coerced(), Some(1500));
+    }
+
+    #[test]
+    fn replace_invalid_escapes() {
+        let options = TokenizerOptions {
+            replace_invalid_escapes: true,
+            ..TokenizerOptions::default()
+        };
+        let json_str = r#"["\uD83D", "x\uDE00\u0041"]"#;
+        let value = Parser::new(Tokenizer::with_options(json_str.chars(), options))
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Array(vec![
+                JsonValue::String("\u{fffd}".to_string()),
+                JsonValue::String("x\u{fffd}A".to_string()),
+            ])
+        );
+        assert!(Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .is_err());
+    }
+
+    #[test]
+    fn byte_order_marks() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert_eq!(
+            parse("\u{feff}[1]"),
+            Ok(JsonValue::Array(vec![JsonValue::Number(1.into())]))
+        );
+
+        let error = parse("[1,\u{feff}2]").unwrap_err();
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::UnexpectedChar {
+                ch: '\u{feff}',
+                line: 1,
+                col: 4
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "illegal byte order mark (U+FEFF) at line 1, column 4 in /1"
+        );
+        assert!(parse("[1]\u{feff}").is_err());
+    }
+
+    #[test]
+    fn decoded_escapes() {
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert_eq!(
+            parse(r#"["\u0041\n\u0042", "\u00e9\\\u0043\"", "\t\u0044"]"#),
+            Ok(JsonValue::Array(vec![
+                JsonValue::String("A\nB".to_string()),
+                JsonValue::String("é\\C\"".to_string()),
+                JsonValue::String("\tD".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        let error = Parser::new(Tokenizer::new("[".repeat(5000).chars()))
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            error.root_cause(),
+            &ParseError::TooDeep {
+                limit: 128,
+                line: 1,
+                col: 129,
+            }
+        );
+        assert!(!error.is_recoverable());
+
+        let options = ParseOptions {
+            max_depth: 2,
+            ..ParseOptions::default()
+        };
+        let parse = |input: &str| {
+            Parser::with_options(Tokenizer::new(input.chars()), options.clone()).parse()
+        };
+        assert!(parse(r#"[{"a":1}, []]"#).is_ok());
+        let error = parse(r#"[{"a":[1]}]"#).unwrap_err();
+        assert_eq!(error.path(), Some("/0/a"));
+        assert_eq!(
+            error.to_string(),
+            "more than 2 nested arrays and objects at line 1, column 7 in /0/a"
+        );
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_map_objects() {
+        let json_str = r#"{"zeta":1,"alpha":2,"mu":3,"alpha":4}"#;
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Keep,
+            ..ParseOptions::default()
+        };
+        let value = Parser::with_options(Tokenizer::new(json_str.chars()), options)
+            .parse()
+            .unwrap();
+        let members = value.as_object().unwrap();
+        assert_eq!(members.len(), 3);
+        assert_eq!(members.get_index_of("alpha"), Some(1));
+        assert_eq!(value.get_typed::<i64>("alpha"), Some(4));
+        assert_eq!(value.to_string(), r#"{"zeta":1,"alpha":4,"mu":3}"#);
+
+        let reordered = Parser::new(Tokenizer::new(r#"{"mu":3,"zeta":1,"alpha":4}"#.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(value, reordered);
+        assert_ne!(value.to_string(), reordered.to_string());
+    }
+}
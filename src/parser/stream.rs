@@ -0,0 +1,194 @@
+//! Push parsing of JSON value streams that arrive in chunks, such as tailed log files.
+
+use crate::error::ParseError;
+use crate::json_value::JsonValue;
+use crate::parser::Parser;
+use crate::tokenizer::Tokenizer;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamError {
+    /// Positions in the error are relative to the end of the last consumed value.
+    pub error: ParseError,
+    /// Bytes of the stream that were consumed by the values returned before the error.
+    pub consumed: usize,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} after {} bytes", self.error, self.consumed)
+    }
+}
+
+impl Error for StreamError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Parses a stream of whitespace separated JSON values, e.g. newline-delimited JSON. Chunks are
+/// handed over with `feed` and complete values taken out with `next_value`.
+#[derive(Debug, Default)]
+pub struct StreamParser {
+    buffer: String,
+    consumed: usize,
+    finished: bool,
+}
+
+impl StreamParser {
+    pub fn new() -> StreamParser {
+        StreamParser::default()
+    }
+
+    pub fn feed(&mut self, chunk: &str) {
+        self.buffer.push_str(chunk);
+    }
+
+    /// Marks the end of the stream, so a value cut off by it is reported as an error instead of
+    /// waiting for more input.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Bytes of the stream consumed by the values returned so far.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Returns the next complete value, or `None` if more input is needed first. After an
+    /// error, `skip_line` can be used to resynchronize at the next line.
+    pub fn next_value(&mut self) -> Option<Result<JsonValue, StreamError>> {
+        if self.buffer.trim_start().is_empty() {
+            return None;
+        }
+
+        let mut parser = Parser::new(Tokenizer::new(self.buffer.chars()));
+        let result = parser.parse_next();
+        let used = self.buffer.len() - parser.remaining().len();
+        match result {
+            // A number running into the end of the buffer may continue in the next chunk.
+            Ok(JsonValue::Number(_)) if used == self.buffer.len() && !self.finished => None,
+            Ok(value) => {
+                self.buffer.drain(..used);
+                self.consumed += used;
+                Some(Ok(value))
+            }
+            // Errors at the very end of the buffer, such as an unterminated string or a cut off
+            // literal, may be resolved by the next chunk.
+            Err(error)
+                if !self.finished
+                    && (parser.remaining().is_empty()
+                        || matches!(error.root_cause(), ParseError::UnexpectedEof { .. })) =>
+            {
+                None
+            }
+            Err(error) => Some(Err(StreamError {
+                error,
+                consumed: self.consumed,
+            })),
+        }
+    }
+
+    /// Discards the buffered input up to and including the line break that ends the line the
+    /// next value starts on, skipping the whitespace left after the previous value first. Returns
+    /// `false` if no complete line is buffered.
+    pub fn skip_line(&mut self) -> bool {
+        let start = self.buffer.len()
+            - self
+                .buffer
+                .trim_start_matches([' ', '\t', '\r', '\n'])
+                .len();
+        match self.buffer[start..].find('\n') {
+            Some(index) => {
+                let index = start + index;
+                self.buffer.drain(..=index);
+                self.consumed += index + 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ParseError;
+    use crate::json_value::{JsonValue, Object};
+    use crate::parser::StreamParser;
+    use crate::tokenizer::TokenType;
+
+    #[test]
+    fn chunks() {
+        let mut stream = StreamParser::new();
+        stream.feed("{\"a\":");
+        assert_eq!(stream.next_value(), None);
+        stream.feed("1}\n12");
+        assert_eq!(
+            stream.next_value(),
+            Some(Ok(JsonValue::Object(Object::from_iter([(
+                "a".to_string(),
+                JsonValue::Number(1.into())
+            )]))))
+        );
+        // The number might still continue.
+        assert_eq!(stream.next_value(), None);
+        stream.feed("3\n");
+        assert_eq!(stream.next_value(), Some(Ok(JsonValue::Number(123.into()))));
+        assert_eq!(stream.next_value(), None);
+        assert_eq!(stream.consumed(), 11);
+
+        // Strings and literals can be cut off anywhere.
+        stream.feed("[\"ab");
+        assert_eq!(stream.next_value(), None);
+        stream.feed("c\", tr");
+        assert_eq!(stream.next_value(), None);
+        stream.feed("ue]");
+        assert_eq!(
+            stream.next_value(),
+            Some(Ok(JsonValue::Array(vec![
+                JsonValue::String("abc".to_string()),
+                JsonValue::Boolean(true)
+            ])))
+        );
+    }
+
+    #[test]
+    fn consumed_offset() {
+        let mut stream = StreamParser::new();
+        stream.feed("{\"a\":1}\n{\"b\":]}\n[true]\n");
+        assert!(matches!(stream.next_value(), Some(Ok(_))));
+
+        let error = stream.next_value().unwrap().unwrap_err();
+        assert_eq!(error.consumed, 7);
+        assert_eq!(
+            error.error.root_cause(),
+            &ParseError::UnexpectedToken {
+                found: TokenType::ArrayEnd,
+                line: 2,
+                col: 6
+            }
+        );
+
+        // Resynchronize at the next line and carry on.
+        assert!(stream.skip_line());
+        assert_eq!(stream.consumed(), 16);
+        assert_eq!(
+            stream.next_value(),
+            Some(Ok(JsonValue::Array(vec![JsonValue::Boolean(true)])))
+        );
+
+        let mut stream = StreamParser::new();
+        stream.feed("1\n{bad}\n2\n");
+        assert_eq!(stream.next_value(), Some(Ok(JsonValue::Number(1.into()))));
+        assert_eq!(stream.next_value().unwrap().unwrap_err().consumed, 1);
+        assert!(stream.skip_line());
+        assert_eq!(stream.next_value(), Some(Ok(JsonValue::Number(2.into()))));
+        assert!(!stream.skip_line());
+
+        stream.feed("[1,");
+        assert_eq!(stream.next_value(), None);
+        stream.finish();
+        assert!(matches!(stream.next_value(), Some(Err(_))));
+    }
+}
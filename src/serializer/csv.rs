@@ -0,0 +1,107 @@
+//! Serialization of arrays of flat objects into CSV.
+
+use crate::json_value::JsonValue;
+
+impl JsonValue {
+    /// Encodes an array of objects as CSV as described by RFC-4180, with `\r\n` line endings.
+    /// The header row holds every key in the order it was first seen, and each object becomes
+    /// one row, leaving the cells of keys it does not have empty.
+    ///
+    /// Strings are used verbatim, `null` becomes an empty cell, and numbers, booleans, arrays and
+    /// objects are written as their compact JSON text. Returns `None` if the value is not an
+    /// array or any element is not an object. An empty array gives an empty string.
+    pub fn to_csv(&self) -> Option<String> {
+        let JsonValue::Array(elements) = self else {
+            return None;
+        };
+
+        let mut rows = vec![];
+        let mut header: Vec<&str> = vec![];
+        for element in elements {
+            let JsonValue::Object(members) = element else {
+                return None;
+            };
+            for (key, _) in members {
+                if !header.contains(&key.as_str()) {
+                    header.push(key);
+                }
+            }
+            rows.push(members);
+        }
+
+        let mut output = String::new();
+        if rows.is_empty() {
+            return Some(output);
+        }
+        write_row(&mut output, header.iter().map(|key| key.to_string()));
+        for members in rows {
+            write_row(
+                &mut output,
+                header.iter().map(|key| {
+                    match members.iter().find(|(member_key, _)| member_key == key) {
+                        Some((_, JsonValue::String(string))) => string.clone(),
+                        Some((_, JsonValue::Null)) | None => String::new(),
+                        Some((_, member)) => member.to_string(),
+                    }
+                }),
+            );
+        }
+        Some(output)
+    }
+}
+
+fn write_row(output: &mut String, cells: impl Iterator<Item = String>) {
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        if cell.contains([',', '"', '\r', '\n']) {
+            output.push('"');
+            output.push_str(&cell.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(&cell);
+        }
+    }
+    output.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn array_of_objects() {
+        let json_str = r#"[
+            {"id":1,"name":"Ada","admin":true},
+            {"id":2,"name":"Smith, \"Bob\"","email":null},
+            {"name":"multi\nline","id":3,"tags":["a","b"],"email":"c@example.com"}
+        ]"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value.to_csv().unwrap(),
+            "id,name,admin,email,tags\r\n\
+             1,Ada,true,,\r\n\
+             2,\"Smith, \"\"Bob\"\"\",,,\r\n\
+             3,\"multi\nline\",,c@example.com,\"[\"\"a\"\",\"\"b\"\"]\"\r\n"
+        );
+    }
+
+    #[test]
+    fn not_a_table() {
+        assert_eq!(JsonValue::Array(vec![]).to_csv(), Some(String::new()));
+        assert_eq!(JsonValue::Object(Object::new()).to_csv(), None);
+        assert_eq!(
+            JsonValue::Array(vec![
+                JsonValue::Object(Object::new()),
+                JsonValue::Number(1.into())
+            ])
+            .to_csv(),
+            None
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//! Module for serializing JSON values back into text.
+
+mod csv;
+mod query_string;
+mod stream;
+
+use crate::json_value::JsonValue;
+use crate::parser::{escape_pointer_token, Comments};
+use std::fmt;
+use std::io;
+
+pub use stream::Serializer;
+
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// Emit object keys in sorted order. The value itself is left untouched.
+    pub sort_keys: bool,
+    /// End the output with a `\n`, as many command line tools expect of files.
+    pub trailing_newline: bool,
+    /// Escape U+2028 and U+2029 as `\u2028` and `\u2029`. Both are valid in JSON strings but
+    /// end a string literal in older JavaScript, so the output can be embedded in scripts.
+    pub escape_line_separators: bool,
+    /// Write strings and keys without escaping them, for values parsed with
+    /// `ParseOptions::keep_escapes`. Any other strings must not contain quotes, backslashes or
+    /// control characters, or the output is not valid JSON.
+    pub keep_escapes: bool,
+    /// Write every array element and object member on its own line, indented by this many
+    /// spaces per level, with a space after colons. Empty arrays and objects stay on one line.
+    pub indent: Option<usize>,
+    /// Escape every non-ASCII character as `\uXXXX`, characters outside the Basic Multilingual
+    /// Plane as a surrogate pair, so the output is pure ASCII.
+    pub ascii_only: bool,
+}
+
+impl JsonValue {
+    pub fn to_string_with(&self, options: &SerializeOptions) -> String {
+        let mut writer = Writer::new(options, None);
+        writer.write_value(self);
+        writer.finish()
+    }
+
+    /// Serializes the value as indented JSON, see `SerializeOptions::indent`.
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        self.to_string_with(&SerializeOptions {
+            indent: Some(indent),
+            ..SerializeOptions::default()
+        })
+    }
+
+    /// Serializes the elements of an array as JSON Lines, each one as compact JSON followed by
+    /// a `\n`. Returns `None` if the value is not an array.
+    pub fn to_json_lines(&self) -> Option<String> {
+        let JsonValue::Array(elements) = self else {
+            return None;
+        };
+        let mut output = String::new();
+        for element in elements {
+            output.push_str(&element.to_string());
+            output.push('\n');
+        }
+        Some(output)
+    }
+
+    pub fn write_to<W: io::Write>(
+        &self,
+        mut writer: W,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        writer.write_all(self.to_string_with(options).as_bytes())
+    }
+
+    /// Serializes the value, emitting the comments collected by `parse_jsonc` in front of the
+    /// values they are attached to.
+    pub fn to_string_with_comments(
+        &self,
+        comments: &Comments,
+        options: &SerializeOptions,
+    ) -> String {
+        let mut writer = Writer::new(options, Some(comments));
+        writer.write_comments();
+        writer.write_value(self);
+        writer.finish()
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_with(&SerializeOptions::default()))
+    }
+}
+
+struct Writer<'a> {
+    output: String,
+    options: &'a SerializeOptions,
+    comments: Option<&'a Comments>,
+    // JSON Pointer to the value being written, only tracked when there are comments to emit.
+    path: String,
+    // Nesting level of the value being written, for indentation.
+    depth: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(options: &'a SerializeOptions, comments: Option<&'a Comments>) -> Writer<'a> {
+        Writer {
+            output: String::new(),
+            options,
+            comments: comments.filter(|comments| !comments.is_empty()),
+            path: String::new(),
+            depth: 0,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.options.trailing_newline {
+            self.output.push('\n');
+        }
+        self.output
+    }
+
+    fn write_value(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Null => self.output.push_str("null"),
+            JsonValue::Boolean(boolean) => {
+                self.output
+                    .push_str(if *boolean { "true" } else { "false" })
+            }
+            JsonValue::Number(number) => self.output.push_str(&number.to_string()),
+            JsonValue::String(string) => write_string(&mut self.output, string, self.options),
+            JsonValue::Array(elements) => {
+                self.output.push('[');
+                self.depth += 1;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push(',');
+                    }
+                    self.newline();
+                    let path_len = self.enter(&i.to_string());
+                    self.write_comments();
+                    self.write_value(element);
+                    self.path.truncate(path_len);
+                }
+                self.depth -= 1;
+                if !elements.is_empty() {
+                    self.newline();
+                }
+                self.output.push(']');
+            }
+            JsonValue::Object(members) => {
+                let mut members: Vec<_> = members.iter().collect();
+                if self.options.sort_keys {
+                    // Stable sort, so duplicate keys keep their relative order.
+                    members.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+                }
+
+                self.output.push('{');
+                self.depth += 1;
+                let empty = members.is_empty();
+                for (i, (key, member)) in members.into_iter().enumerate() {
+                    if i > 0 {
+                        self.output.push(',');
+                    }
+                    self.newline();
+                    let path_len = self.enter(&escape_pointer_token(key));
+                    self.write_comments();
+                    write_string(&mut self.output, key, self.options);
+                    self.output.push(':');
+                    if self.options.indent.is_some() {
+                        self.output.push(' ');
+                    }
+                    self.write_value(member);
+                    self.path.truncate(path_len);
+                }
+                self.depth -= 1;
+                if !empty {
+                    self.newline();
+                }
+                self.output.push('}');
+            }
+        }
+    }
+
+    // Descends into a child of the current value, returning the length to truncate the path
+    // back to afterwards.
+    fn enter(&mut self, token: &str) -> usize {
+        let path_len = self.path.len();
+        if self.comments.is_some() {
+            self.path.push('/');
+            self.path.push_str(token);
+        }
+        path_len
+    }
+
+    fn write_comments(&mut self) {
+        let Some(comments) = self.comments.and_then(|comments| comments.get(&self.path)) else {
+            return;
+        };
+        for comment in comments {
+            self.output.push_str(comment);
+            if self.options.indent.is_some() {
+                self.newline();
+            } else if comment.starts_with("//") {
+                // A line comment would otherwise swallow whatever comes after it.
+                self.output.push('\n');
+            }
+        }
+    }
+
+    // Starts a new line at the current indentation when pretty-printing.
+    fn newline(&mut self) {
+        if let Some(indent) = self.options.indent {
+            self.output.push('\n');
+            self.output.push_str(&" ".repeat(indent * self.depth));
+        }
+    }
+}
+
+fn write_string(output: &mut String, string: &str, options: &SerializeOptions) {
+    output.push('"');
+    if options.keep_escapes {
+        output.push_str(string);
+        output.push('"');
+        return;
+    }
+    for ch in string.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            // Remaining control characters have no short form.
+            '\u{0}'..='\u{1f}' => output.push_str(&format!("\\u{:04x}", ch as u32)),
+            '\u{2028}' | '\u{2029}' if options.escape_line_separators => {
+                output.push_str(&format!("\\u{:04x}", ch as u32))
+            }
+            _ if options.ascii_only && !ch.is_ascii() => {
+                for unit in ch.encode_utf16(&mut [0; 2]) {
+                    output.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            _ => output.push(ch),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+    use crate::parser::{parse_many, DuplicateKeys, ParseOptions, Parser};
+    use crate::serializer::SerializeOptions;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn sort_keys() {
+        let object = JsonValue::Object(Object::from_iter([
+            ("zebra".to_string(), JsonValue::Number(1.into())),
+            (
+                "apple".to_string(),
+                JsonValue::Object(Object::from_iter([
+                    ("y".to_string(), JsonValue::Null),
+                    ("x".to_string(), JsonValue::Boolean(true)),
+                ])),
+            ),
+            ("mango".to_string(), JsonValue::String("ripe".to_string())),
+        ]));
+
+        assert_eq!(
+            object.to_string(),
+            r#"{"zebra":1,"apple":{"y":null,"x":true},"mango":"ripe"}"#
+        );
+
+        let options = SerializeOptions {
+            sort_keys: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(
+            object.to_string_with(&options),
+            r#"{"apple":{"x":true,"y":null},"mango":"ripe","zebra":1}"#
+        );
+
+        // The tree itself must not be reordered.
+        assert_eq!(
+            object.to_string(),
+            r#"{"zebra":1,"apple":{"y":null,"x":true},"mango":"ripe"}"#
+        );
+    }
+
+    #[test]
+    fn key_order_round_trip() {
+        let keys = [
+            "zeta", "alpha", "mu", "beta", "omega", "delta", "kappa", "eta", "a", "z",
+        ];
+        let mut json_str = String::from("{");
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                json_str.push(',');
+            }
+            // Nest a reversed copy of the keys in every member, with a duplicate key at the end
+            // where objects can hold one.
+            let duplicate = cfg!(not(feature = "indexmap")).then_some(key);
+            let nested: Vec<String> = keys
+                .iter()
+                .rev()
+                .chain(duplicate)
+                .map(|nested_key| format!(r#""{}":[{{"{}":{}}}]"#, nested_key, key, i))
+                .collect();
+            json_str.push_str(&format!(r#""{}":{{{}}}"#, key, nested.join(",")));
+        }
+        json_str.push('}');
+
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Keep,
+            ..ParseOptions::default()
+        };
+        let parse = |input: &str| {
+            Parser::with_options(Tokenizer::new(input.chars()), options.clone()).parse()
+        };
+        let value = parse(&json_str).unwrap();
+        let serialized = value.to_string();
+        assert_eq!(serialized, json_str);
+        assert_eq!(parse(&serialized).unwrap(), value);
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let documents = [
+            r#"{"a":[1,-2,true,false,null],"b":{},"c":[],"d":"text"}"#,
+            r#""quote \" backslash \\ slash /""#,
+            r#""\b\f\n\r\t\u0000\u001f é 😀""#,
+            r#"[[[]],{"":{"":null}}]"#,
+            "0",
+        ];
+        for json_str in documents {
+            let value = Parser::new(Tokenizer::new(json_str.chars()))
+                .parse()
+                .unwrap();
+            assert_eq!(value.to_string(), json_str);
+        }
+
+        // Whitespace is dropped and control characters are escaped.
+        let value = JsonValue::String("\u{1}\"\\".to_string());
+        assert_eq!(value.to_string(), r#""\u0001\"\\""#);
+        let value = Parser::new(Tokenizer::new("[ 1 , { \"a\" : \"\\u0041\" } ]".chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(value.to_string(), r#"[1,{"a":"A"}]"#);
+    }
+
+    #[test]
+    fn pretty() {
+        let json_str =
+            r#"{"name":"hdjson","tags":["json",[]],"nested":{"empty":{},"list":[1,{"a":null}]}}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value.to_pretty_string(2),
+            r#"{
+  "name": "hdjson",
+  "tags": [
+    "json",
+    []
+  ],
+  "nested": {
+    "empty": {},
+    "list": [
+      1,
+      {
+        "a": null
+      }
+    ]
+  }
+}"#
+        );
+        assert_eq!(
+            JsonValue::Array(vec![JsonValue::Boolean(true)]).to_pretty_string(4),
+            "[\n    true\n]"
+        );
+        assert_eq!(JsonValue::Object(Object::new()).to_pretty_string(2), "{}");
+        assert_eq!(JsonValue::Null.to_pretty_string(2), "null");
+        // Indenting by zero still breaks lines.
+        assert_eq!(
+            JsonValue::Array(vec![JsonValue::Null, JsonValue::Null]).to_pretty_string(0),
+            "[\nnull,\nnull\n]"
+        );
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let array = JsonValue::Array(vec![JsonValue::Number(1.into()), JsonValue::Null]);
+        assert_eq!(array.to_string(), "[1,null]");
+        assert_eq!(
+            array.to_string_with(&SerializeOptions::default()),
+            "[1,null]"
+        );
+
+        let options = SerializeOptions {
+            trailing_newline: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(array.to_string_with(&options), "[1,null]\n");
+
+        let mut output = vec![];
+        array.write_to(&mut output, &options).unwrap();
+        assert_eq!(output, b"[1,null]\n");
+    }
+
+    #[test]
+    fn escape_line_separators() {
+        let string = JsonValue::String("a\u{2028}b\u{2029}c".to_string());
+        assert_eq!(string.to_string(), "\"a\u{2028}b\u{2029}c\"");
+
+        let options = SerializeOptions {
+            escape_line_separators: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(string.to_string_with(&options), r#""a\u2028b\u2029c""#);
+    }
+
+    #[test]
+    fn ascii_only() {
+        let string = JsonValue::String("é 😀 a".to_string());
+        assert_eq!(string.to_string(), "\"é 😀 a\"");
+
+        let options = SerializeOptions {
+            ascii_only: true,
+            ..SerializeOptions::default()
+        };
+        let escaped = string.to_string_with(&options);
+        assert_eq!(escaped, r#""\u00e9 \ud83d\ude00 a""#);
+        let parse = |input: &str| Parser::new(Tokenizer::new(input.chars())).parse();
+        assert_eq!(parse(&escaped), Ok(string));
+    }
+
+    #[test]
+    fn json_lines() {
+        let json_str = r#"[{"id":1,"tags":["a"]}, "two", 3, null]"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        let lines = value.to_json_lines().unwrap();
+        assert_eq!(lines, "{\"id\":1,\"tags\":[\"a\"]}\n\"two\"\n3\nnull\n");
+
+        let JsonValue::Array(elements) = value else {
+            unreachable!();
+        };
+        assert_eq!(parse_many(&lines), Ok(elements));
+        assert_eq!(JsonValue::Null.to_json_lines(), None);
+    }
+}
@@ -0,0 +1,96 @@
+//! Serialization of flat objects into `application/x-www-form-urlencoded` strings.
+
+use crate::json_value::JsonValue;
+
+impl JsonValue {
+    /// Encodes a flat object as a query string, e.g. `{"a":1,"b":"hi there"}` becomes
+    /// `a=1&b=hi+there`. Strings are used verbatim, numbers and booleans by their JSON text and
+    /// `null` as an empty value.
+    ///
+    /// Returns `None` if the value is not an object or any member is an array or object, since
+    /// form encoding has no notion of nesting.
+    pub fn to_query_string(&self) -> Option<String> {
+        let JsonValue::Object(members) = self else {
+            return None;
+        };
+
+        let mut output = String::new();
+        for (i, (key, member)) in members.iter().enumerate() {
+            let value = match member {
+                JsonValue::Null => String::new(),
+                JsonValue::Boolean(boolean) => boolean.to_string(),
+                JsonValue::Number(number) => number.to_string(),
+                JsonValue::String(string) => string.clone(),
+                JsonValue::Array(_) | JsonValue::Object(_) => return None,
+            };
+
+            if i > 0 {
+                output.push('&');
+            }
+            encode_component(&mut output, key);
+            output.push('=');
+            encode_component(&mut output, &value);
+        }
+        Some(output)
+    }
+}
+
+fn encode_component(output: &mut String, component: &str) {
+    for byte in component.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => {
+                output.push(byte as char)
+            }
+            b' ' => output.push('+'),
+            _ => output.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+
+    #[test]
+    fn flat_object() {
+        let object = JsonValue::Object(Object::from_iter([
+            ("a".to_string(), JsonValue::Number(1.into())),
+            ("b".to_string(), JsonValue::String("hello".to_string())),
+            ("c".to_string(), JsonValue::Boolean(false)),
+            ("d".to_string(), JsonValue::Null),
+        ]));
+        assert_eq!(
+            object.to_query_string(),
+            Some("a=1&b=hello&c=false&d=".to_string())
+        );
+        assert_eq!(
+            JsonValue::Object(Object::new()).to_query_string(),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn special_characters() {
+        let object = JsonValue::Object(Object::from_iter([
+            (
+                "q & a".to_string(),
+                JsonValue::String("1+1=2 / ok?".to_string()),
+            ),
+            ("café".to_string(), JsonValue::String("50%".to_string())),
+        ]));
+        assert_eq!(
+            object.to_query_string(),
+            Some("q+%26+a=1%2B1%3D2+%2F+ok%3F&caf%C3%A9=50%25".to_string())
+        );
+    }
+
+    #[test]
+    fn nested_values() {
+        let object = JsonValue::Object(Object::from_iter([(
+            "list".to_string(),
+            JsonValue::Array(vec![JsonValue::Number(1.into())]),
+        )]));
+        assert_eq!(object.to_query_string(), None);
+        assert_eq!(JsonValue::Number(1.into()).to_query_string(), None);
+    }
+}
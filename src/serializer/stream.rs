@@ -0,0 +1,339 @@
+//! Incremental serialization, for emitting documents without building a `JsonValue` first.
+
+use crate::json_value::JsonValue;
+use crate::serializer::{write_string, SerializeOptions, Writer};
+use std::io;
+
+enum Container {
+    Array { empty: bool },
+    Object { empty: bool, has_key: bool },
+}
+
+/// Writes a single JSON value piece by piece, placing the commas and colons itself. Calls that
+/// would produce invalid JSON, such as a value in an object without a key, fail with an
+/// `io::ErrorKind::InvalidInput` error and write nothing.
+///
+/// ```
+/// use hdjson::{JsonValue, Serializer};
+///
+/// let mut serializer = Serializer::new(vec![]);
+/// serializer.begin_object().unwrap();
+/// serializer.key("a").unwrap();
+/// serializer.value(&JsonValue::Null).unwrap();
+/// serializer.end_object().unwrap();
+/// assert_eq!(serializer.finish().unwrap(), br#"{"a":null}"#);
+/// ```
+pub struct Serializer<W: io::Write> {
+    writer: W,
+    options: SerializeOptions,
+    stack: Vec<Container>,
+    // Whether the top-level value has been written completely.
+    done: bool,
+}
+
+impl<W: io::Write> Serializer<W> {
+    pub fn new(writer: W) -> Serializer<W> {
+        Serializer::with_options(writer, SerializeOptions::default())
+    }
+
+    /// `sort_keys` only applies to objects handed over to `value`, keys passed to `key` are
+    /// written as they come. `indent` applies to the whole document.
+    pub fn with_options(writer: W, options: SerializeOptions) -> Serializer<W> {
+        Serializer {
+            writer,
+            options,
+            stack: vec![],
+            done: false,
+        }
+    }
+
+    pub fn begin_object(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.stack.push(Container::Object {
+            empty: true,
+            has_key: false,
+        });
+        self.writer.write_all(b"{")
+    }
+
+    pub fn end_object(&mut self) -> io::Result<()> {
+        match self.stack.last() {
+            Some(Container::Object { has_key: false, .. }) => {}
+            Some(Container::Object { has_key: true, .. }) => {
+                return Err(invalid("key without a value"))
+            }
+            Some(Container::Array { .. }) => return Err(invalid("end of object inside an array")),
+            None => return Err(invalid("end of object outside of an object")),
+        }
+        if let Some(Container::Object { empty: false, .. }) = self.stack.pop() {
+            self.newline()?;
+        }
+        self.after_value();
+        self.writer.write_all(b"}")
+    }
+
+    pub fn begin_array(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.stack.push(Container::Array { empty: true });
+        self.writer.write_all(b"[")
+    }
+
+    pub fn end_array(&mut self) -> io::Result<()> {
+        match self.stack.last() {
+            Some(Container::Array { .. }) => {}
+            Some(Container::Object { .. }) => return Err(invalid("end of array inside an object")),
+            None => return Err(invalid("end of array outside of an array")),
+        }
+        if let Some(Container::Array { empty: false }) = self.stack.pop() {
+            self.newline()?;
+        }
+        self.after_value();
+        self.writer.write_all(b"]")
+    }
+
+    /// Writes the key of the next member of the current object.
+    pub fn key(&mut self, key: &str) -> io::Result<()> {
+        let Some(Container::Object { empty, has_key }) = self.stack.last_mut() else {
+            return Err(invalid("key outside of an object"));
+        };
+        if *has_key {
+            return Err(invalid("key without a value"));
+        }
+        let mut output = String::new();
+        if !*empty {
+            output.push(',');
+        }
+        *empty = false;
+        *has_key = true;
+        if let Some(indent) = self.options.indent {
+            output.push('\n');
+            output.push_str(&" ".repeat(indent * self.stack.len()));
+        }
+        write_string(&mut output, key, &self.options);
+        output.push(':');
+        if self.options.indent.is_some() {
+            output.push(' ');
+        }
+        self.writer.write_all(output.as_bytes())
+    }
+
+    /// Writes a complete value, as the top-level value, the next element of the current array
+    /// or the value of the key written last.
+    pub fn value(&mut self, value: &JsonValue) -> io::Result<()> {
+        self.before_value()?;
+        let mut writer = Writer::new(&self.options, None);
+        // Nested values are indented relative to the containers that are still open.
+        writer.depth = self.stack.len();
+        writer.write_value(value);
+        let output = writer.output;
+        self.after_value();
+        self.writer.write_all(output.as_bytes())
+    }
+
+    /// Checks that the top-level value is complete and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.stack.last() {
+            Some(Container::Array { .. }) => return Err(invalid("unclosed array")),
+            Some(Container::Object { .. }) => return Err(invalid("unclosed object")),
+            None if !self.done => return Err(invalid("no value written")),
+            None => {}
+        }
+        if self.options.trailing_newline {
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(self.writer)
+    }
+
+    // Checks that a value may come next and writes the comma in front of array elements.
+    fn before_value(&mut self) -> io::Result<()> {
+        match self.stack.last_mut() {
+            Some(Container::Array { empty }) => {
+                if !*empty {
+                    self.writer.write_all(b",")?;
+                }
+                *empty = false;
+                self.newline()?;
+            }
+            Some(Container::Object { has_key, .. }) => {
+                if !*has_key {
+                    return Err(invalid("value without a key"));
+                }
+                *has_key = false;
+            }
+            None if self.done => return Err(invalid("more than one top-level value")),
+            None => {}
+        }
+        Ok(())
+    }
+
+    // Starts a new line at the depth of the current container when pretty-printing.
+    fn newline(&mut self) -> io::Result<()> {
+        match self.options.indent {
+            Some(indent) => {
+                let line = format!("\n{}", " ".repeat(indent * self.stack.len()));
+                self.writer.write_all(line.as_bytes())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn after_value(&mut self) {
+        if self.stack.is_empty() {
+            self.done = true;
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+    use crate::serializer::{SerializeOptions, Serializer};
+    use std::io;
+
+    #[test]
+    fn nested_document() {
+        let mut serializer = Serializer::new(vec![]);
+        serializer.begin_object().unwrap();
+        serializer.key("name").unwrap();
+        serializer
+            .value(&JsonValue::String("a \"b\"".to_string()))
+            .unwrap();
+        serializer.key("items").unwrap();
+        serializer.begin_array().unwrap();
+        serializer.value(&JsonValue::Number(1.into())).unwrap();
+        serializer.begin_object().unwrap();
+        serializer.end_object().unwrap();
+        serializer.begin_array().unwrap();
+        serializer.end_array().unwrap();
+        serializer
+            .value(&JsonValue::Array(vec![JsonValue::Null]))
+            .unwrap();
+        serializer.end_array().unwrap();
+        serializer.key("ok").unwrap();
+        serializer.value(&JsonValue::Boolean(true)).unwrap();
+        serializer.end_object().unwrap();
+        assert_eq!(
+            serializer.finish().unwrap(),
+            br#"{"name":"a \"b\"","items":[1,{},[],[null]],"ok":true}"#
+        );
+
+        let options = SerializeOptions {
+            trailing_newline: true,
+            ..SerializeOptions::default()
+        };
+        let mut serializer = Serializer::with_options(vec![], options);
+        serializer.value(&JsonValue::Null).unwrap();
+        assert_eq!(serializer.finish().unwrap(), b"null\n");
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        let error = |calls: &dyn Fn(&mut Serializer<Vec<u8>>) -> io::Result<()>| {
+            let mut serializer = Serializer::new(vec![]);
+            let error = calls(&mut serializer).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            error.to_string()
+        };
+        let null = &JsonValue::Null;
+
+        assert_eq!(
+            error(&|s| {
+                s.begin_object()?;
+                s.value(null)
+            }),
+            "value without a key"
+        );
+        assert_eq!(error(&|s| s.key("a")), "key outside of an object");
+        assert_eq!(
+            error(&|s| {
+                s.begin_object()?;
+                s.key("a")?;
+                s.key("b")
+            }),
+            "key without a value"
+        );
+        assert_eq!(
+            error(&|s| {
+                s.begin_object()?;
+                s.key("a")?;
+                s.end_object()
+            }),
+            "key without a value"
+        );
+        assert_eq!(
+            error(&|s| {
+                s.begin_array()?;
+                s.end_object()
+            }),
+            "end of object inside an array"
+        );
+        assert_eq!(
+            error(&|s| s.end_array()),
+            "end of array outside of an array"
+        );
+        assert_eq!(
+            error(&|s| {
+                s.value(null)?;
+                s.begin_array()
+            }),
+            "more than one top-level value"
+        );
+
+        let mut serializer = Serializer::new(vec![]);
+        serializer.begin_array().unwrap();
+        assert!(serializer.value(&JsonValue::Null).is_ok());
+        assert_eq!(
+            serializer.finish().unwrap_err().to_string(),
+            "unclosed array"
+        );
+        let serializer = Serializer::new(vec![]);
+        assert_eq!(
+            serializer.finish().unwrap_err().to_string(),
+            "no value written"
+        );
+    }
+
+    #[test]
+    fn indent() {
+        let options = SerializeOptions {
+            indent: Some(2),
+            ..SerializeOptions::default()
+        };
+        let mut serializer = Serializer::with_options(vec![], options);
+        serializer.begin_array().unwrap();
+        serializer
+            .value(&JsonValue::Array(vec![
+                JsonValue::Number(1.into()),
+                JsonValue::Number(2.into()),
+            ]))
+            .unwrap();
+        serializer.begin_object().unwrap();
+        serializer.key("a").unwrap();
+        serializer.begin_array().unwrap();
+        serializer.end_array().unwrap();
+        serializer.key("b").unwrap();
+        serializer.value(&JsonValue::Null).unwrap();
+        serializer.end_object().unwrap();
+        serializer.end_array().unwrap();
+
+        let expected = JsonValue::Array(vec![
+            JsonValue::Array(vec![
+                JsonValue::Number(1.into()),
+                JsonValue::Number(2.into()),
+            ]),
+            JsonValue::Object(Object::from_iter([
+                ("a".to_string(), JsonValue::Array(vec![])),
+                ("b".to_string(), JsonValue::Null),
+            ])),
+        ]);
+        let output = serializer.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            expected.to_pretty_string(2)
+        );
+    }
+}
@@ -1,32 +1,209 @@
 //! Module for performing tokenization of JSON inputs.
 
-mod json_value;
 mod token;
 
+use crate::error::ParseError;
 pub use crate::tokenizer::token::Token;
 pub use crate::tokenizer::token::TokenType;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::mem;
+use std::ops::Range;
 use std::str::Chars;
 
+#[derive(Debug, Clone)]
+pub struct TokenizerOptions {
+    /// Accept `//` and `/* */` comments and emit them as `TokenType::Comment` tokens.
+    pub jsonc: bool,
+    /// Number of columns a `\t` advances the position by, to match how editors display tabs.
+    pub tab_width: i32,
+    /// Stop with `ParseError::TooManyTokens` once more than this many tokens were produced,
+    /// bounding the work spent on an input regardless of its shape.
+    pub max_tokens: Option<usize>,
+    /// Report `ParseError::EmptyDocument` if the input holds nothing but whitespace, instead of
+    /// just producing no tokens.
+    pub reject_empty: bool,
+    /// Accept `_` between two digits of a number, as in `1_000`. The underscores are left out
+    /// of the token. Without this option any underscore in a number is an error.
+    pub digit_underscores: bool,
+    /// Stop with `ParseError::NumberTooLong` at a number with more digits than this, counting
+    /// the integer, fraction and exponent parts together.
+    pub max_number_digits: Option<usize>,
+    /// Accept JSON5 hexadecimal integers such as `0x1F` or `-0XFF`. They are emitted as
+    /// `TokenType::Integer` holding the decimal value, so `0x1F` becomes `"31"`. The prefix has
+    /// to follow a single `0`, `00x1` is not a hexadecimal number. Without this option a `0x`
+    /// prefix is an error.
+    pub hex_numbers: bool,
+    /// Replace malformed `\u` escapes and unpaired surrogates with U+FFFD instead of failing,
+    /// like browsers do. The replacement character is put into the token as it is, other invalid
+    /// escapes such as `\q` are still an error.
+    pub replace_invalid_escapes: bool,
+    /// Count lines and columns from 0 instead of 1, as LSP and some editors do. Applies to token
+    /// positions, errors and `offset_to_line_col` alike.
+    pub zero_based_positions: bool,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> TokenizerOptions {
+        TokenizerOptions {
+            jsonc: false,
+            tab_width: 1,
+            max_tokens: None,
+            reject_empty: false,
+            digit_underscores: false,
+            max_number_digits: None,
+            hex_numbers: false,
+            replace_invalid_escapes: false,
+            zero_based_positions: false,
+        }
+    }
+}
+
 pub struct Tokenizer<'a> {
+    input: &'a str,
     source: Chars<'a>,
+    options: TokenizerOptions,
+    // Byte offsets at which each line of the input starts, built on first use.
+    line_starts: OnceCell<Vec<usize>>,
+    // Position of the start of the input, see `starting_at`.
+    first_line: i32,
+    first_col: i32,
     current_col: i32,
+    // How far the column moves with the next character, depends on the current one.
+    col_advance: i32,
     current_line: i32,
     token_start_col: i32,
+    token_start_line: i32,
     current_char: Option<char>,
+    token_count: usize,
+    // Byte offset of the first character of the current token.
+    token_start: usize,
+    error: Option<ParseError>,
+    // The result of the last `peek_token`, handed out by the next `next_token`.
+    peeked: Option<Option<Token<'a>>>,
+    // Whether the input is pure ASCII, which enables faster scanning of strings.
+    ascii: bool,
 }
 
-impl Tokenizer<'_> {
-    pub fn new(input: Chars) -> Tokenizer {
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: Chars<'a>) -> Tokenizer<'a> {
+        Tokenizer::with_options(input, TokenizerOptions::default())
+    }
+
+    // `FromStr` can't be implemented instead, its result can't borrow from the input.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'a str) -> Tokenizer<'a> {
+        Tokenizer::new(input.chars())
+    }
+
+    /// Tokenizes UTF-8 encoded bytes, failing with `ParseError::InvalidUtf8` up front if they
+    /// aren't valid UTF-8.
+    pub fn from_bytes(input: &'a [u8]) -> Result<Tokenizer<'a>, ParseError> {
+        match std::str::from_utf8(input) {
+            Ok(input) => Ok(Tokenizer::from_str(input)),
+            Err(error) => Err(ParseError::InvalidUtf8 {
+                offset: error.valid_up_to(),
+            }),
+        }
+    }
+
+    pub fn with_options(input: Chars<'a>, options: TokenizerOptions) -> Tokenizer<'a> {
+        let base = if options.zero_based_positions { 0 } else { 1 };
         Tokenizer {
+            input: input.as_str(),
+            ascii: input.as_str().is_ascii(),
             source: input,
-            current_col: 0,
-            current_line: 1,
+            options,
+            line_starts: OnceCell::new(),
+            first_line: base,
+            first_col: base,
+            current_col: base - 1,
+            col_advance: 1,
+            current_line: base,
             token_start_col: 0,
+            token_start_line: 1,
             current_char: None,
+            token_count: 0,
+            token_start: 0,
+            error: None,
+            peeked: None,
+        }
+    }
+
+    /// Offsets all positions for an input that is a fragment of a larger document, starting at
+    /// `line` and `col` of it. Only the first line is shifted by `col`, the following ones
+    /// start at the first column as usual. Must be called before any tokens are read.
+    pub fn starting_at(mut self, line: i32, col: i32) -> Tokenizer<'a> {
+        self.first_line = line;
+        self.first_col = col;
+        self.current_line = line;
+        self.current_col = col - 1;
+        self
+    }
+
+    /// Byte offset of the start of every line in the input, the first line starting at 0.
+    pub fn line_starts(&self) -> &[usize] {
+        self.line_starts.get_or_init(|| {
+            let newlines = self.input.match_indices('\n').map(|(i, _)| i + 1);
+            std::iter::once(0).chain(newlines).collect()
+        })
+    }
+
+    /// Converts a byte offset in the input into a line and column, counting them the same way
+    /// token positions do. Offsets past the end are clamped to it.
+    pub fn offset_to_line_col(&self, offset: usize) -> (i32, i32) {
+        let offset = offset.min(self.input.len());
+        let line_starts = self.line_starts();
+        let line = match line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        let mut col = if line == 0 {
+            self.first_col
+        } else {
+            self.first_column()
+        };
+        for (i, ch) in self.input[line_starts[line]..].char_indices() {
+            if line_starts[line] + i >= offset {
+                break;
+            }
+            col += if ch == '\t' {
+                self.options.tab_width
+            } else {
+                1
+            };
         }
+        (self.first_line + line as i32, col)
+    }
+
+    /// The error that made `next_token` return `None`, if that was caused by malformed input
+    /// rather than the end of it.
+    pub fn error(&self) -> Option<&ParseError> {
+        self.error.as_ref()
+    }
+
+    pub(crate) fn remaining(&self) -> &'a str {
+        self.source.as_str()
+    }
+
+    // Byte offset of the next character to be read.
+    fn offset(&self) -> usize {
+        self.input.len() - self.source.as_str().len()
+    }
+
+    pub(crate) fn line(&self) -> i32 {
+        self.current_line
+    }
+
+    pub(crate) fn column(&self) -> i32 {
+        self.current_col
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    /// Reads all tokens, stopping at the first malformed one. Prefer `try_tokenize`, which reports
+    /// that as an error, unless a partial list is useful, e.g. for highlighting; `error` then
+    /// tells whether the list is complete.
+    pub fn tokenize(&mut self) -> Vec<Token<'a>> {
         let mut tokens = vec![];
         while let Some(token) = self.next_token() {
             tokens.push(token);
@@ -34,12 +211,71 @@ impl Tokenizer<'_> {
         tokens
     }
 
+    /// Reads all tokens, failing if the input is malformed anywhere.
+    pub fn try_tokenize(&mut self) -> Result<Vec<Token<'a>>, ParseError> {
+        let tokens = self.tokenize();
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(tokens),
+        }
+    }
+
+    /// Like `tokenize`, but pairs every token with the byte range it covers in the input.
+    pub fn tokenize_with_spans(&mut self) -> Vec<(Token<'a>, Range<usize>)> {
+        let mut tokens = vec![];
+        while let Some(token) = self.next_token() {
+            tokens.push((token, self.token_start..self.offset()));
+        }
+        tokens
+    }
+
+    /// Consumes the next token, failing unless it is of the same kind as `expected`. The
+    /// contents of number, string, boolean and comment tokens are not compared.
+    pub fn expect(&mut self, expected: TokenType<'_>) -> Result<Token<'a>, ParseError> {
+        match self.next_token() {
+            Some(token) if mem::discriminant(&token.token_type) == mem::discriminant(&expected) => {
+                Ok(token)
+            }
+            Some(token) => Err(ParseError::UnexpectedToken {
+                found: token.token_type.into_owned(),
+                line: token.line,
+                col: token.position,
+            }),
+            None => Err(ParseError::UnexpectedEof {
+                line: self.current_line,
+                col: self.current_col,
+            }),
+        }
+    }
+
+    // Number of the first column of a line, see `TokenizerOptions::zero_based_positions`.
+    fn first_column(&self) -> i32 {
+        if self.options.zero_based_positions {
+            0
+        } else {
+            1
+        }
+    }
+
+    // The character the next `next_char` will return.
+    fn peek_char(&self) -> Option<char> {
+        self.source.clone().next()
+    }
+
     pub fn next_char(&mut self) -> Option<char> {
-        self.current_col += 1;
+        // Columns start over after a line break.
+        if self.current_char == Some('\n') {
+            self.current_col = self.first_column();
+        } else {
+            self.current_col += self.col_advance;
+        }
+        self.col_advance = 1;
         let next = self.source.next();
         if let Some(ch) = next {
             if ch == '\n' {
                 self.current_line += 1;
+            } else if ch == '\t' {
+                self.col_advance = self.options.tab_width;
             }
         }
 
@@ -47,214 +283,463 @@ impl Tokenizer<'_> {
         next
     }
 
-    pub fn next_token(&mut self) -> Option<Token> {
+    /// Returns the token the next call to `next_token` will return, without consuming it. Only
+    /// the first peek reads from the input, so positions advance once per token either way.
+    pub fn peek_token(&mut self) -> Option<&Token<'a>> {
+        if self.peeked.is_none() {
+            let token = self.next_token();
+            self.peeked = Some(token);
+        }
+        self.peeked.as_ref().and_then(Option::as_ref)
+    }
+
+    pub fn next_token(&mut self) -> Option<Token<'a>> {
+        if let Some(peeked) = self.peeked.take() {
+            return peeked;
+        }
+        let Some(token) = self.read_token() else {
+            // Without a current character the input ran out rather than being malformed.
+            if self.options.reject_empty && self.token_count == 0 && self.current_char.is_none() {
+                return self.fail(ParseError::EmptyDocument);
+            }
+            return None;
+        };
+        self.token_count += 1;
+        match self.options.max_tokens {
+            Some(limit) if self.token_count > limit => self.fail(ParseError::TooManyTokens {
+                limit,
+                line: token.line,
+                col: token.position,
+            }),
+            _ => Some(token),
+        }
+    }
+
+    fn read_token(&mut self) -> Option<Token<'a>> {
         let next_char = self.skip_whitespaces();
         if let Some(ch) = next_char {
+            self.token_start = self.offset() - ch.len_utf8();
+            self.token_start_col = self.current_col;
+            self.token_start_line = self.current_line;
             return match ch {
-                '{' => Some(Token::new(TokenType::ObjectStart, self.current_col)),
-                '}' => Some(Token::new(TokenType::ObjectEnd, self.current_col)),
-                '[' => Some(Token::new(TokenType::ArrayStart, self.current_col)),
-                ']' => Some(Token::new(TokenType::ArrayEnd, self.current_col)),
-                ':' => Some(Token::new(TokenType::Colon, self.current_col)),
-                ',' => Some(Token::new(TokenType::Comma, self.current_col)),
-                '0'..='9' => {
-                    self.token_start_col = self.current_col;
-                    self.tokenize_number(true)
-                }
-                '"' => {
-                    self.token_start_col = self.current_col;
-                    self.tokenize_string()
+                '{' => Some(self.token(TokenType::ObjectStart)),
+                '}' => Some(self.token(TokenType::ObjectEnd)),
+                '[' => Some(self.token(TokenType::ArrayStart)),
+                ']' => Some(self.token(TokenType::ArrayEnd)),
+                ':' => Some(self.token(TokenType::Colon)),
+                ',' => Some(self.token(TokenType::Comma)),
+                '0'..='9' => self.tokenize_number(true),
+                '"' => self.tokenize_string(),
+                't' | 'f' | 'n' => self.tokenize_literal(),
+                '/' if self.options.jsonc => self.tokenize_comment(),
+                // A common mistake, so it gets a clearer error than any other character.
+                '/' if matches!(self.peek_char(), Some('/' | '*')) => {
+                    self.fail(ParseError::CommentNotAllowed {
+                        line: self.current_line,
+                        col: self.current_col,
+                    })
                 }
                 '-' => {
-                    self.token_start_col = self.current_col;
-                    if let Some(_next) = self.next_char() {
+                    // The minus must be immediately followed by a digit, `- 5` is not a number.
+                    if let Some('0'..='9') = self.next_char() {
                         self.tokenize_number(false)
                     } else {
-                        // TODO: Syntax error, minus must be followed by a number.
-                        None
+                        self.fail(self.invalid_number())
                     }
                 }
-                _ => {
-                    // TODO: Make some actually helpful errors.
-                    None
-                }
+                // A byte order mark is only tolerated at the very start of the input.
+                '\u{feff}' if self.token_start == 0 => self.read_token(),
+                '\u{feff}' => self.fail(ParseError::UnexpectedChar {
+                    ch,
+                    line: self.current_line,
+                    col: self.current_col,
+                }),
+                _ => self.fail(ParseError::UnexpectedChar {
+                    ch,
+                    line: self.current_line,
+                    col: self.current_col,
+                }),
             };
         }
         None
     }
 
-    fn tokenize_number(&mut self, positive: bool) -> Option<Token> {
-        /* TODO: Include support for exponents. */
-        if let Some(mut number) = self.handle_integer() {
-            // If we encounter a dot, we know that we're dealing with a floating point number.
-            let char_count = number.chars().count();
-            for _ in 1..char_count {
-                self.next_char();
-            }
-            if let Some(next_char) = self.source.clone().next() {
-                if next_char != '.' && next_char != 'e' {
-                    if !positive {
-                        number = '-'.to_string() + number.as_str();
-                    }
-                    return Some(Token::new(TokenType::Integer(number), self.token_start_col));
-                }
+    // Creates a token starting where the current one started.
+    fn token(&self, token_type: TokenType<'a>) -> Token<'a> {
+        Token::new(token_type, self.token_start_line, self.token_start_col)
+    }
 
-                // We encountered a dot, get the decimal part and stitch them together.
-                let is_float = next_char == '.';
-                self.next_char();
-                if let Some(decimal_part) = self.handle_integer() {
-                    if decimal_part.chars().count() == 1 {
-                        // handle_integer only returned a dot.
-                        return None;
-                    }
-                    number = number + decimal_part.as_str();
-                    let char_count = decimal_part.chars().count();
-                    for _ in 1..char_count {
-                        self.next_char();
-                    }
-                    if !positive {
-                        number = '-'.to_string() + number.as_str();
-                    }
+    fn invalid_number(&self) -> ParseError {
+        ParseError::InvalidNumber {
+            line: self.token_start_line,
+            col: self.token_start_col,
+        }
+    }
 
-                    if let Some(next_char) = self.source.clone().next() {
-                        if next_char != 'e' {
-                            return if is_float {
-                                Some(Token::new(TokenType::Float(number), self.token_start_col))
-                            } else {
-                                Some(Token::new(TokenType::Integer(number), self.token_start_col))
-                            };
-                        }
+    fn unterminated_string(&self) -> ParseError {
+        ParseError::UnterminatedString {
+            line: self.token_start_line,
+            col: self.token_start_col,
+        }
+    }
 
-                        // We encountered an exponent, get the decimal part and stitch them together.
-                        self.next_char();
-                        if let Some(is_signed) = self.source.clone().next() {
-                            if is_signed == '-' || is_signed == '+' {
-                                number.push('e');
-                                self.next_char();
-                            }
-                        }
-                        if let Some(decimal_part) = self.handle_integer() {
-                            number = number + decimal_part.as_str();
-                            let char_count = decimal_part.chars().count();
-                            for _ in 1..char_count {
-                                self.next_char();
-                            }
-                            return if is_float {
-                                Some(Token::new(TokenType::Float(number), self.token_start_col))
-                            } else {
-                                Some(Token::new(TokenType::Integer(number), self.token_start_col))
-                            };
-                        }
-                    } else {
-                        return None;
-                    }
+    // Records why tokenization stopped, for `error` to report.
+    fn fail<T>(&mut self, error: ParseError) -> Option<T> {
+        self.error = Some(error);
+        None
+    }
 
-                    return Some(Token::new(TokenType::Float(number), self.token_start_col));
-                }
-            } else {
-                return None;
+    fn tokenize_number(&mut self, positive: bool) -> Option<Token<'a>> {
+        let mut number = String::new();
+        self.handle_integer(&mut number)?;
+        if number == "0" && matches!(self.peek_char(), Some('x' | 'X')) {
+            return self.tokenize_hex(positive);
+        }
+
+        // If we encounter a dot or an exponent, we know that we're dealing with a floating point
+        // number.
+        let mut is_float = self.peek_char() == Some('.');
+        if is_float {
+            self.next_char();
+            // A misplaced underscore is reported by handle_integer.
+            if !matches!(self.peek_char(), Some('0'..='9' | '_')) {
+                // Point at the dot rather than the number.
+                return self.fail(ParseError::InvalidNumber {
+                    line: self.current_line,
+                    col: self.current_col,
+                });
             }
+            self.handle_integer(&mut number)?;
         }
 
-        return None;
-    }
+        if let Some(exponent @ ('e' | 'E')) = self.peek_char() {
+            // We encountered an exponent, it is kept exactly as written, sign included.
+            is_float = true;
+            self.next_char();
+            number.push(exponent);
+            if let Some(sign @ ('-' | '+')) = self.peek_char() {
+                self.next_char();
+                number.push(sign);
+            }
+            if !matches!(self.peek_char(), Some('0'..='9')) {
+                return self.fail(self.invalid_number());
+            }
+            self.next_char();
+            self.handle_integer(&mut number)?;
+        }
 
-    fn handle_integer(&mut self) -> Option<String> {
-        let first_digit: char;
-        if let Some(digit) = self.current_char {
-            first_digit = digit;
+        if let Some(limit) = self.options.max_number_digits {
+            if number.chars().filter(char::is_ascii_digit).count() > limit {
+                return self.fail(ParseError::NumberTooLong {
+                    limit,
+                    line: self.token_start_line,
+                    col: self.token_start_col,
+                });
+            }
+        }
+        if !positive {
+            number.insert(0, '-');
+        }
+        let token_type = if is_float {
+            TokenType::Float(number)
         } else {
-            return None;
+            TokenType::Integer(number)
+        };
+        Some(self.token(token_type))
+    }
+
+    // Reads a hexadecimal integer, the current character being the `0` of its `0x` prefix.
+    fn tokenize_hex(&mut self, positive: bool) -> Option<Token<'a>> {
+        if !self.options.hex_numbers {
+            return self.fail(self.invalid_number());
+        }
+        self.next_char();
+        let mut digits = String::new();
+        if !positive {
+            digits.push('-');
+        }
+        while let Some(digit) = self.peek_char().filter(char::is_ascii_hexdigit) {
+            self.next_char();
+            digits.push(digit);
         }
+        match i64::from_str_radix(&digits, 16) {
+            Ok(value) => Some(self.token(TokenType::Integer(value.to_string()))),
+            // No digits at all, or a value that does not fit in an i64.
+            Err(_) => self.fail(self.invalid_number()),
+        }
+    }
 
-        let mut result = String::from(first_digit);
-        let mut iter = self.source.clone();
-        while let Some(digit) = iter.next() {
-            match digit {
-                '0'..='9' => {
-                    result.push(digit);
-                }
-                _ => return Some(result),
+    // Appends the current character and the digits that follow it to `number`, leaving the
+    // tokenizer on the last digit. Underscores between digits are skipped if allowed. Callers
+    // must have checked the current character, which is a digit or the dot of a fraction.
+    fn handle_integer(&mut self, number: &mut String) -> Option<()> {
+        debug_assert!(
+            matches!(self.current_char, Some('0'..='9' | '.')),
+            "number starting with {:?}",
+            self.current_char
+        );
+        number.push(self.current_char?);
+        while let Some(next) = self.peek_char() {
+            match next {
+                '0'..='9' => number.push(next),
+                '_' if self.options.digit_underscores
+                    && number.ends_with(|ch: char| ch.is_ascii_digit())
+                    && matches!(self.source.clone().nth(1), Some('0'..='9')) => {}
+                '_' => return self.fail(self.invalid_number()),
+                _ => break,
             }
+            self.next_char();
         }
-
-        Some(result)
+        Some(())
     }
 
-    fn tokenize_string(&mut self) -> Option<Token> {
-        let mut string_val = String::new();
+    fn tokenize_literal(&mut self) -> Option<Token<'a>> {
+        let mut literal = String::new();
+        literal.push(self.current_char?);
+        while let Some(next_char) = self.peek_char() {
+            if !next_char.is_ascii_alphabetic() {
+                break;
+            }
+            literal.push(next_char);
+            self.next_char();
+        }
 
-        let mut iter = self.source.clone();
-        let mut skip = 0;
-        while let Some(next_char) = iter.next() {
-            if next_char.is_control() {
-                return None;
+        let token_type = match literal.as_str() {
+            "true" => TokenType::Boolean(true),
+            "false" => TokenType::Boolean(false),
+            "null" => TokenType::Null,
+            _ => {
+                // Report the first character, it does not start any valid token.
+                return self.fail(ParseError::UnexpectedChar {
+                    ch: literal.chars().next()?,
+                    line: self.current_line,
+                    col: self.token_start_col,
+                });
             }
+        };
+        Some(self.token(token_type))
+    }
 
-            if skip > 0 {
-                skip -= 1;
-                continue;
+    fn tokenize_comment(&mut self) -> Option<Token<'a>> {
+        let mut comment = String::from("/");
+        match self.next_char() {
+            Some('/') => {
+                comment.push('/');
+                // Line comments run until the end of the line, the line break itself is
+                // left for skip_whitespaces.
+                while let Some(next_char) = self.peek_char() {
+                    if next_char == '\n' || next_char == '\r' {
+                        break;
+                    }
+                    comment.push(next_char);
+                    self.next_char();
+                }
             }
-            if next_char == '\\' {
-                self.next_char();
-                if let Some(escape) = self.handle_escapes() {
-                    string_val += escape.as_str();
-                    // Some iterations will need to be skipped, because this loop is using a
-                    // cloned iterator that, handle_escapes() operates on the original one.
-                    skip = escape.chars().count() - 1;
-                } else {
-                    // TODO: Display error.
+            Some('*') => {
+                comment.push('*');
+                loop {
+                    let Some(next_char) = self.next_char() else {
+                        return self.fail(ParseError::UnexpectedEof {
+                            line: self.current_line,
+                            col: self.current_col,
+                        });
+                    };
+                    comment.push(next_char);
+                    if next_char == '*' && self.peek_char() == Some('/') {
+                        comment.push('/');
+                        self.next_char();
+                        break;
+                    }
                 }
-                continue;
             }
+            _ => {
+                return self.fail(ParseError::UnexpectedChar {
+                    ch: '/',
+                    line: self.token_start_line,
+                    col: self.token_start_col,
+                })
+            }
+        }
+        Some(self.token(TokenType::Comment(comment)))
+    }
 
-            if next_char != '"' {
-                string_val.push(next_char);
-                self.next_char();
-            } else {
+    fn tokenize_string(&mut self) -> Option<Token<'a>> {
+        // Escapes are kept as written, so the contents are borrowed from the input unless an
+        // escape had to be replaced, see `TokenizerOptions::replace_invalid_escapes`.
+        let start = self.offset();
+        let mut replaced: Option<String> = None;
+        loop {
+            if self.ascii {
+                let plain = self.skip_plain_ascii();
+                if let Some(replaced) = &mut replaced {
+                    replaced.push_str(plain);
+                }
+            }
+            let Some(next_char) = self.peek_char() else {
                 break;
+            };
+
+            // Only U+0000 to U+001F have to be escaped, see RFC-8259 section 7.
+            if next_char <= '\u{1f}' {
+                self.next_char();
+                // A line break is reported at the end of the line it breaks.
+                let line = match next_char {
+                    '\n' => self.current_line - 1,
+                    _ => self.current_line,
+                };
+                return self.fail(ParseError::ControlCharInString {
+                    ch: next_char,
+                    line,
+                    col: self.current_col,
+                });
+            }
+
+            match next_char {
+                '\\' => {
+                    let escape_start = self.offset();
+                    self.next_char();
+                    let escape = self.handle_escapes()?;
+                    // A replaced escape no longer starts with a backslash.
+                    if replaced.is_none() && !escape.starts_with('\\') {
+                        replaced = Some(self.input[start..escape_start].to_string());
+                    }
+                    if let Some(replaced) = &mut replaced {
+                        replaced.push_str(&escape);
+                    }
+                }
+                '"' => break,
+                _ => {
+                    if let Some(replaced) = &mut replaced {
+                        replaced.push(next_char);
+                    }
+                    self.next_char();
+                }
             }
         }
-        if let Some(next_char) = self.next_char() {
-            // String values must end with a " quotation mark.
-            return if next_char == '\"' {
-                Some(Token::new(
-                    TokenType::String(string_val.clone()),
-                    self.token_start_col,
-                ))
-            } else {
-                None
-            };
+        let end = self.offset();
+        // String values must end with a " quotation mark.
+        match self.next_char() {
+            Some('"') => {
+                let contents = match replaced {
+                    Some(replaced) => Cow::Owned(replaced),
+                    None => Cow::Borrowed(&self.input[start..end]),
+                };
+                Some(self.token(TokenType::String(contents)))
+            }
+            _ => self.fail(self.unterminated_string()),
         }
-        None
     }
 
+    // Fast path for ASCII inputs, where every byte is a character: skips the characters up to
+    // the next quote, backslash or control character in one go instead of one by one, and
+    // returns them.
+    fn skip_plain_ascii(&mut self) -> &'a str {
+        let rest = self.source.as_str();
+        let len = rest
+            .bytes()
+            .position(|byte| byte == b'"' || byte == b'\\' || byte <= 0x1f)
+            .unwrap_or(rest.len());
+        let (plain, rest) = rest.split_at(len);
+        if len == 0 {
+            return plain;
+        }
+
+        self.source = rest.chars();
+        // Like `len` calls to `next_char`. The current character is never a line break here and
+        // none of the skipped ones is one or a tab.
+        self.current_col += self.col_advance + len as i32 - 1;
+        self.col_advance = 1;
+        self.current_char = plain.chars().next_back();
+        plain
+    }
+
+    // Reads the escape sequence whose backslash is the current character.
     fn handle_escapes(&mut self) -> Option<String> {
-        if let Some(next) = self.next_char() {
-            let mut escape = String::from("\\");
-            escape.push(next);
-            return match next {
-                '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => Some(escape),
-                'u' => {
-                    for _ in 0..4 {
-                        if let Some(seq_char) = self.next_char() {
-                            if !seq_char.is_ascii_hexdigit() {
-                                return None;
-                            }
-                            escape.push(seq_char);
-                        } else {
-                            return None;
+        let (line, col) = (self.current_line, self.current_col);
+        let invalid_escape = |found: &str| ParseError::InvalidEscape {
+            found: found.to_string(),
+            line,
+            col,
+        };
+        let Some(next) = self.next_char() else {
+            return self.fail(self.unterminated_string());
+        };
+
+        let mut escape = String::from("\\");
+        escape.push(next);
+        match next {
+            '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => Some(escape),
+            'u' => {
+                let Some(code_unit) = self.read_code_unit(&mut escape, invalid_escape)? else {
+                    return Some(char::REPLACEMENT_CHARACTER.to_string());
+                };
+                match code_unit {
+                    // A high surrogate must be followed by an escaped low surrogate, the pair
+                    // encodes a single character. Both escapes are kept in the token.
+                    0xd800..=0xdbff if self.followed_by_low_surrogate() => {
+                        self.next_char();
+                        self.next_char();
+                        escape.push_str(r"\u");
+                        self.read_code_unit(&mut escape, invalid_escape)?;
+                    }
+                    0xd800..=0xdfff if self.options.replace_invalid_escapes => {
+                        return Some(char::REPLACEMENT_CHARACTER.to_string());
+                    }
+                    0xd800..=0xdbff => {
+                        // Include the escape that follows instead of a low surrogate, if any.
+                        if self.next_char() == Some('\\') && self.next_char() == Some('u') {
+                            escape.push_str(r"\u");
+                            self.read_code_unit(&mut escape, invalid_escape)?;
                         }
+                        return self.fail(invalid_escape(&escape));
                     }
-                    Some(escape)
+                    0xdc00..=0xdfff => return self.fail(invalid_escape(&escape)),
+                    _ => {}
                 }
-                _ => {
-                    // TODO: Error handling, illegal escape sequence.
-                    None
+                Some(escape)
+            }
+            _ => self.fail(invalid_escape(&escape)),
+        }
+    }
+
+    // Reads the four hex digits of a `\u` escape, appending them to `escape`. Gives `Some(None)`
+    // for malformed digits if `replace_invalid_escapes` is set, the offending character is then
+    // left for the rest of the string.
+    fn read_code_unit(
+        &mut self,
+        escape: &mut String,
+        invalid_escape: impl Fn(&str) -> ParseError,
+    ) -> Option<Option<u32>> {
+        let start = escape.len();
+        for _ in 0..4 {
+            match self.peek_char() {
+                Some(seq_char) if seq_char.is_ascii_hexdigit() => {
+                    self.next_char();
+                    escape.push(seq_char);
                 }
-            };
+                Some(_) if self.options.replace_invalid_escapes => return Some(None),
+                Some(seq_char) => {
+                    self.next_char();
+                    escape.push(seq_char);
+                    return self.fail(invalid_escape(escape));
+                }
+                None => {
+                    self.next_char();
+                    return self.fail(self.unterminated_string());
+                }
+            }
         }
-        None
+        Some(u32::from_str_radix(&escape[start..], 16).ok())
+    }
+
+    // Whether the next characters are the escape of a low surrogate, `\uDC00` to `\uDFFF`.
+    fn followed_by_low_surrogate(&self) -> bool {
+        let rest = self.source.as_str();
+        let Some(hex) = rest.strip_prefix(r"\u").and_then(|rest| rest.get(..4)) else {
+            return false;
+        };
+        hex.bytes().all(|byte| byte.is_ascii_hexdigit())
+            && (0xdc00..=0xdfff).contains(&u32::from_str_radix(hex, 16).unwrap_or(0))
     }
 
     fn skip_whitespaces(&mut self) -> Option<char> {
@@ -272,9 +757,21 @@ impl Tokenizer<'_> {
     }
 }
 
+/// Yields tokens one at a time, like repeated calls to `next_token`. The iterator ends at the end
+/// of the input or at the first malformed token, `error` tells the two apart.
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.next_token()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::tokenizer::{Token, TokenType, Tokenizer};
+    use crate::error::ParseError;
+    use crate::tokenizer::{Token, TokenType, Tokenizer, TokenizerOptions};
+    use std::borrow::Cow;
 
     #[test]
     fn simple_objects() {
@@ -282,18 +779,19 @@ mod tests {
         let mut lexer = Tokenizer::new(json_str.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ObjectStart, 1),
-            Token::new(TokenType::String("coolness_factor".to_string()), 2),
-            Token::new(TokenType::Colon, 19),
-            Token::new(TokenType::Integer("2".to_string()), 20),
-            Token::new(TokenType::Comma, 21),
-            Token::new(TokenType::String("description".to_string()), 22),
-            Token::new(TokenType::Colon, 35),
+            Token::new(TokenType::ObjectStart, 1, 1),
+            Token::new(TokenType::String("coolness_factor".into()), 1, 2),
+            Token::new(TokenType::Colon, 1, 19),
+            Token::new(TokenType::Integer("2".to_string()), 1, 20),
+            Token::new(TokenType::Comma, 1, 21),
+            Token::new(TokenType::String("description".into()), 1, 22),
+            Token::new(TokenType::Colon, 1, 35),
             Token::new(
-                TokenType::String("This is kinda \\\"cool\\\"!".to_string()),
+                TokenType::String("This is kinda \\\"cool\\\"!".into()),
+                1,
                 36,
             ),
-            Token::new(TokenType::ObjectEnd, 61),
+            Token::new(TokenType::ObjectEnd, 1, 61),
         ];
         assert_eq!(tokens, expected_tokens);
     }
@@ -304,34 +802,163 @@ mod tests {
         let mut lexer = Tokenizer::new(json_str.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ObjectStart, 1),
-            Token::new(TokenType::String("allowed".to_string()), 2),
-            Token::new(TokenType::Colon, 11),
-            Token::new(TokenType::String("\\u0009".to_string()), 12),
-            Token::new(TokenType::Comma, 20),
-            Token::new(TokenType::String("allowed1".to_string()), 21),
-            Token::new(TokenType::Colon, 31),
-            Token::new(TokenType::String("\\b".to_string()), 32),
-            Token::new(TokenType::Comma, 36),
-            Token::new(TokenType::String("allowed2".to_string()), 37),
-            Token::new(TokenType::Colon, 47),
-            Token::new(TokenType::String("\\n".to_string()), 48),
-            Token::new(TokenType::Comma, 52),
-            Token::new(TokenType::String("allowed3".to_string()), 53),
-            Token::new(TokenType::Colon, 63),
-            Token::new(TokenType::String("\\\\".to_string()), 64),
-            Token::new(TokenType::ObjectEnd, 68),
+            Token::new(TokenType::ObjectStart, 1, 1),
+            Token::new(TokenType::String("allowed".into()), 1, 2),
+            Token::new(TokenType::Colon, 1, 11),
+            Token::new(TokenType::String("\\u0009".into()), 1, 12),
+            Token::new(TokenType::Comma, 1, 20),
+            Token::new(TokenType::String("allowed1".into()), 1, 21),
+            Token::new(TokenType::Colon, 1, 31),
+            Token::new(TokenType::String("\\b".into()), 1, 32),
+            Token::new(TokenType::Comma, 1, 36),
+            Token::new(TokenType::String("allowed2".into()), 1, 37),
+            Token::new(TokenType::Colon, 1, 47),
+            Token::new(TokenType::String("\\n".into()), 1, 48),
+            Token::new(TokenType::Comma, 1, 52),
+            Token::new(TokenType::String("allowed3".into()), 1, 53),
+            Token::new(TokenType::Colon, 1, 63),
+            Token::new(TokenType::String("\\\\".into()), 1, 64),
+            Token::new(TokenType::ObjectEnd, 1, 68),
         ];
         assert_eq!(tokens, expected_tokens);
     }
 
+    #[test]
+    fn decoded_value() {
+        let json_str = r#"["\u0041", "a\tb\u00e9\\", 1]"#;
+        let tokens = Tokenizer::new(json_str.chars()).tokenize();
+        assert_eq!(
+            tokens[1].token_type(),
+            &TokenType::String(r#"\u0041"#.into())
+        );
+        assert_eq!(tokens[1].decoded_value(), Some("A".into()));
+        assert_eq!(tokens[3].decoded_value(), Some("a\tbé\\".into()));
+        assert_eq!(tokens[5].decoded_value(), None);
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        let json_str = r#""a\uD83D\uDCA9b""#;
+        let mut lexer = Tokenizer::new(json_str.chars());
+        assert_eq!(
+            lexer.try_tokenize(),
+            Ok(vec![Token::new(
+                TokenType::String(r#"a\uD83D\uDCA9b"#.into()),
+                1,
+                1
+            )])
+        );
+
+        let unpaired = [
+            (r#""x\uD83D""#, r"\uD83D"),
+            (r#""x\uD83Dx""#, r"\uD83D"),
+            (r#""x\uD83D\u0041""#, r"\uD83D\u0041"),
+            (r#""x\uD83D\uD83D""#, r"\uD83D\uD83D"),
+            (r#""x\uDCA9""#, r"\uDCA9"),
+        ];
+        for (input, found) in unpaired {
+            let mut lexer = Tokenizer::new(input.chars());
+            assert_eq!(
+                lexer.try_tokenize(),
+                Err(ParseError::InvalidEscape {
+                    found: found.to_string(),
+                    line: 1,
+                    col: 3
+                }),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn constructors() {
+        let json_str = r#"{"a": [1, "é"]}"#;
+        let expected = Tokenizer::new(json_str.chars()).tokenize();
+        assert_eq!(Tokenizer::from_str(json_str).tokenize(), expected);
+        assert_eq!(
+            Tokenizer::from_bytes(json_str.as_bytes())
+                .unwrap()
+                .tokenize(),
+            expected
+        );
+        assert_eq!(
+            Tokenizer::from_bytes(b"[\"a\xff\"]").err(),
+            Some(ParseError::InvalidUtf8 { offset: 3 })
+        );
+    }
+
+    #[test]
+    fn borrowed_strings() {
+        let json_str = r#"["plain", "esc\u0041", "a\uD83Db"]"#;
+        let tokens = Tokenizer::new(json_str.chars()).tokenize();
+        assert!(matches!(
+            tokens[1].token_type(),
+            TokenType::String(Cow::Borrowed("plain"))
+        ));
+        assert!(matches!(
+            tokens[1].decoded_value(),
+            Some(Cow::Borrowed("plain"))
+        ));
+        assert!(matches!(
+            tokens[3].token_type(),
+            TokenType::String(Cow::Borrowed(_))
+        ));
+        assert!(matches!(tokens[3].decoded_value(), Some(Cow::Owned(_))));
+
+        // Only replacing an escape copies the contents.
+        let options = TokenizerOptions {
+            replace_invalid_escapes: true,
+            ..TokenizerOptions::default()
+        };
+        let tokens = Tokenizer::with_options(json_str.chars(), options).tokenize();
+        assert!(matches!(
+            tokens[3].token_type(),
+            TokenType::String(Cow::Borrowed(_))
+        ));
+        assert!(matches!(
+            tokens[5].token_type(),
+            TokenType::String(Cow::Owned(_))
+        ));
+    }
+
+    #[test]
+    fn replace_invalid_escapes() {
+        let options = TokenizerOptions {
+            replace_invalid_escapes: true,
+            ..TokenizerOptions::default()
+        };
+        let strings = [
+            (r#""a\uD83Db""#, "a\u{fffd}b"),
+            (r#""\uDCA9""#, "\u{fffd}"),
+            (r#""\uD83D\u0041""#, "\u{fffd}\\u0041"),
+            (r#""\uD83D\uD83D\uDCA9""#, "\u{fffd}\\uD83D\\uDCA9"),
+            (r#""\u12G4""#, "\u{fffd}G4"),
+            (r#""\u12""#, "\u{fffd}"),
+            (r#""\uD83D\uDCA9""#, "\\uD83D\\uDCA9"),
+        ];
+        for (input, expected) in strings {
+            let mut lexer = Tokenizer::with_options(input.chars(), options.clone());
+            assert_eq!(
+                lexer.try_tokenize(),
+                Ok(vec![Token::new(TokenType::String(expected.into()), 1, 1)]),
+                "{}",
+                input
+            );
+        }
+
+        let mut lexer = Tokenizer::with_options(r#""\q""#.chars(), options);
+        assert!(lexer.try_tokenize().is_err());
+    }
+
     #[test]
     fn strings() {
         let complete_string = r#""This string is completed and should be tokenized.""#;
         let mut lexer = Tokenizer::new(complete_string.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![Token::new(
-            TokenType::String("This string is completed and should be tokenized.".to_string()),
+            TokenType::String("This string is completed and should be tokenized.".into()),
+            1,
             1,
         )];
         assert_eq!(tokens, expected_tokens);
@@ -339,40 +966,90 @@ mod tests {
         let incomplete_string =
             r#""This string is missing a quotation mark at the end and should not be tokenized."#;
         let mut lexer = Tokenizer::new(incomplete_string.chars());
-        let tokens = lexer.tokenize();
-        let expected_tokens = vec![Token::new(
-            TokenType::String(
-                "This string is missing a quotation mark at the end and should not be tokenized.}"
-                    .to_string(),
-            ),
-            1,
-        )];
-        assert_ne!(tokens, expected_tokens);
+        assert_eq!(
+            lexer.try_tokenize(),
+            Err(ParseError::UnterminatedString { line: 1, col: 1 })
+        );
 
         let money_is_fire = r#"{"money": "💶=🔥"}"#;
         let mut lexer = Tokenizer::new(money_is_fire.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ObjectStart, 1),
-            Token::new(TokenType::String("money".to_string()), 2),
-            Token::new(TokenType::Colon, 9),
-            Token::new(TokenType::String("💶=🔥".to_string()), 11),
-            Token::new(TokenType::ObjectEnd, 16),
+            Token::new(TokenType::ObjectStart, 1, 1),
+            Token::new(TokenType::String("money".into()), 1, 2),
+            Token::new(TokenType::Colon, 1, 9),
+            Token::new(TokenType::String("💶=🔥".into()), 1, 11),
+            Token::new(TokenType::ObjectEnd, 1, 16),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // Control characters are fine once escaped.
+        let ctrlseq = r#"{"ctrlseq": "\n"}"#;
+        let mut lexer = Tokenizer::new(ctrlseq.chars());
+        let tokens = lexer.tokenize();
+        let expected_tokens = vec![
+            Token::new(TokenType::ObjectStart, 1, 1),
+            Token::new(TokenType::String("ctrlseq".into()), 1, 2),
+            Token::new(TokenType::Colon, 1, 11),
+            Token::new(TokenType::String("\\n".into()), 1, 13),
+            Token::new(TokenType::ObjectEnd, 1, 17),
         ];
         assert_eq!(tokens, expected_tokens);
+    }
 
-        let ctrlseq = r#"{"ctrlseq": "
-        "}"#;
+    #[test]
+    fn raw_control_characters() {
+        // U+0000 to U+001F must be escaped inside strings, a raw line break ends tokenization.
+        let ctrlseq = "{\"ctrlseq\": \"\n\"}";
         let mut lexer = Tokenizer::new(ctrlseq.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ObjectStart, 1),
-            Token::new(TokenType::String("ctrlseq".to_string()), 2),
-            Token::new(TokenType::Colon, 11),
-            Token::new(TokenType::String("💶=🔥".to_string()), 13),
-            Token::new(TokenType::ObjectEnd, 16),
+            Token::new(TokenType::ObjectStart, 1, 1),
+            Token::new(TokenType::String("ctrlseq".into()), 1, 2),
+            Token::new(TokenType::Colon, 1, 11),
         ];
-        assert_ne!(tokens, expected_tokens);
+        assert_eq!(tokens, expected_tokens);
+
+        for ch in ['\u{0}', '\t', '\n', '\r', '\u{1f}'] {
+            let json_str = format!("[\"a{}b\"]", ch);
+            let mut lexer = Tokenizer::new(json_str.chars());
+            assert_eq!(
+                lexer.try_tokenize(),
+                Err(ParseError::ControlCharInString {
+                    ch,
+                    line: 1,
+                    col: 4
+                }),
+                "{:?}",
+                ch
+            );
+        }
+        let mut lexer = Tokenizer::new("{\n  \"a\": \"tab\there\"\n}".chars());
+        let error = lexer.try_tokenize().unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::ControlCharInString {
+                ch: '\t',
+                line: 2,
+                col: 12
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "unescaped control character U+0009 in string at line 2, column 12"
+        );
+
+        // Other control characters, such as DEL and the C1 range, may appear as they are.
+        for ch in ['\u{7f}', '\u{85}', '\u{9f}'] {
+            let json_str = format!("\"a{}b\"", ch);
+            let mut lexer = Tokenizer::new(json_str.chars());
+            let expected_tokens = vec![Token::new(
+                TokenType::String(format!("a{}b", ch).into()),
+                1,
+                1,
+            )];
+            assert_eq!(lexer.tokenize(), expected_tokens, "{:?}", ch);
+        }
     }
 
     #[test]
@@ -381,13 +1058,13 @@ mod tests {
         let mut lexer = Tokenizer::new(integer.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ArrayStart, 1),
-            Token::new(TokenType::Integer("5".to_string()), 2),
-            Token::new(TokenType::Comma, 3),
-            Token::new(TokenType::Integer("-10".to_string()), 5),
-            Token::new(TokenType::Comma, 8),
-            Token::new(TokenType::Integer("-928472".to_string()), 10),
-            Token::new(TokenType::ArrayEnd, 17),
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Integer("5".to_string()), 1, 2),
+            Token::new(TokenType::Comma, 1, 3),
+            Token::new(TokenType::Integer("-10".to_string()), 1, 5),
+            Token::new(TokenType::Comma, 1, 8),
+            Token::new(TokenType::Integer("-928472".to_string()), 1, 10),
+            Token::new(TokenType::ArrayEnd, 1, 17),
         ];
         assert_eq!(tokens, expected_tokens);
 
@@ -395,11 +1072,11 @@ mod tests {
         let mut lexer = Tokenizer::new(float.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ArrayStart, 1),
-            Token::new(TokenType::Float("5.23".to_string()), 2),
-            Token::new(TokenType::Comma, 6),
-            Token::new(TokenType::Float("-23.0923787687".to_string()), 8),
-            Token::new(TokenType::ArrayEnd, 22),
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Float("5.23".to_string()), 1, 2),
+            Token::new(TokenType::Comma, 1, 6),
+            Token::new(TokenType::Float("-23.0923787687".to_string()), 1, 8),
+            Token::new(TokenType::ArrayEnd, 1, 22),
         ];
         assert_eq!(tokens, expected_tokens);
 
@@ -407,30 +1084,819 @@ mod tests {
         let mut lexer = Tokenizer::new(exponents.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![
-            Token::new(TokenType::ArrayStart, 1),
-            Token::new(TokenType::Integer("1e2".to_string()), 2),
-            Token::new(TokenType::Comma, 5),
-            Token::new(TokenType::Float("1.0e2".to_string()), 7),
-            Token::new(TokenType::Comma, 12),
-            Token::new(TokenType::Float("2.0879878e243".to_string()), 14),
-            Token::new(TokenType::Comma, 27),
-            Token::new(TokenType::Float("-32.928e-54".to_string()), 29),
-            Token::new(TokenType::Comma, 40),
-            Token::new(TokenType::Float("-32.928e+54".to_string()), 42),
-            Token::new(TokenType::ArrayEnd, 53),
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Float("1e2".to_string()), 1, 2),
+            Token::new(TokenType::Comma, 1, 5),
+            Token::new(TokenType::Float("1.0e2".to_string()), 1, 7),
+            Token::new(TokenType::Comma, 1, 12),
+            Token::new(TokenType::Float("2.0879878e243".to_string()), 1, 14),
+            Token::new(TokenType::Comma, 1, 27),
+            Token::new(TokenType::Float("-32.928e-54".to_string()), 1, 29),
+            Token::new(TokenType::Comma, 1, 40),
+            Token::new(TokenType::Float("-32.928e+54".to_string()), 1, 42),
+            Token::new(TokenType::ArrayEnd, 1, 53),
         ];
         assert_eq!(tokens, expected_tokens);
 
-        let do_not_recognize = r#"5."#;
+        for (input, col) in [("5.", 2), ("-3.", 3), ("1.e5", 2), (r#"{"x":5.}"#, 7)] {
+            let mut lexer = Tokenizer::new(input.chars());
+            assert_eq!(
+                lexer.try_tokenize(),
+                Err(ParseError::InvalidNumber { line: 1, col }),
+                "{}",
+                input
+            );
+        }
+
+        let do_not_recognize = r#"+5"#;
         let mut lexer = Tokenizer::new(do_not_recognize.chars());
         let tokens = lexer.tokenize();
         let expected_tokens = vec![];
         assert_eq!(tokens, expected_tokens);
+    }
 
-        let do_not_recognize = r#"+5"#;
-        let mut lexer = Tokenizer::new(do_not_recognize.chars());
+    #[test]
+    fn number_entry_points() {
+        // Every way into a number must start it with a digit, which `handle_integer` asserts.
+        let options = TokenizerOptions {
+            digit_underscores: true,
+            ..TokenizerOptions::default()
+        };
+        for input in [
+            "-", "-a", "- 5", ".5", "1e", "1e+", "1e_5", "1._5", "-_1", "1.-5",
+        ] {
+            let mut lexer = Tokenizer::with_options(input.chars(), options.clone());
+            assert!(lexer.try_tokenize().is_err(), "{}", input);
+        }
+        for (input, expected) in [
+            ("0", TokenType::Integer("0".to_string())),
+            ("-0", TokenType::Integer("-0".to_string())),
+            ("1_0.5_0e-1_2", TokenType::Float("10.50e-12".to_string())),
+        ] {
+            let mut lexer = Tokenizer::with_options(input.chars(), options.clone());
+            assert_eq!(
+                lexer.try_tokenize(),
+                Ok(vec![Token::new(expected, 1, 1)]),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn literals() {
+        let json_str = r#"[true, false, null, nil]"#;
+        let mut lexer = Tokenizer::new(json_str.chars());
         let tokens = lexer.tokenize();
-        let expected_tokens = vec![];
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Boolean(true), 1, 2),
+            Token::new(TokenType::Comma, 1, 6),
+            Token::new(TokenType::Boolean(false), 1, 8),
+            Token::new(TokenType::Comma, 1, 13),
+            Token::new(TokenType::Null, 1, 15),
+            Token::new(TokenType::Comma, 1, 19),
+        ];
+        assert_eq!(tokens, expected_tokens);
+    }
+
+    #[test]
+    fn strict_comments() {
+        let mut lexer = Tokenizer::new("{\"a\":1 // x\n}".chars());
+        let error = lexer.try_tokenize().unwrap_err();
+        assert_eq!(error, ParseError::CommentNotAllowed { line: 1, col: 8 });
+        assert_eq!(
+            error.to_string(),
+            "comments are not allowed in strict JSON at line 1, column 8"
+        );
+
+        let mut lexer = Tokenizer::new("[1,\n  /* x */ 2]".chars());
+        assert_eq!(
+            lexer.try_tokenize(),
+            Err(ParseError::CommentNotAllowed { line: 2, col: 3 })
+        );
+        // A lone slash is just an unexpected character.
+        let mut lexer = Tokenizer::new("[1 / 2]".chars());
+        assert_eq!(
+            lexer.try_tokenize(),
+            Err(ParseError::UnexpectedChar {
+                ch: '/',
+                line: 1,
+                col: 4
+            })
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let json_str = "// leading\n[1, /* inline */ 2]";
+        let mut lexer = Tokenizer::new(json_str.chars());
+        assert_eq!(lexer.tokenize(), vec![]);
+
+        let options = TokenizerOptions {
+            jsonc: true,
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options(json_str.chars(), options);
+        let tokens = lexer.tokenize();
+        let expected_tokens = vec![
+            Token::new(TokenType::Comment("// leading".to_string()), 1, 1),
+            Token::new(TokenType::ArrayStart, 2, 1),
+            Token::new(TokenType::Integer("1".to_string()), 2, 2),
+            Token::new(TokenType::Comma, 2, 3),
+            Token::new(TokenType::Comment("/* inline */".to_string()), 2, 5),
+            Token::new(TokenType::Integer("2".to_string()), 2, 18),
+            Token::new(TokenType::ArrayEnd, 2, 19),
+        ];
+        assert_eq!(tokens, expected_tokens);
+    }
+
+    #[test]
+    fn expect() {
+        let json_str = r#"{"key": 5}"#;
+        let mut lexer = Tokenizer::new(json_str.chars());
+        assert_eq!(
+            lexer.expect(TokenType::ObjectStart),
+            Ok(Token::new(TokenType::ObjectStart, 1, 1))
+        );
+        // Only the kind of token matters, not its contents.
+        assert_eq!(
+            lexer.expect(TokenType::String("".into())),
+            Ok(Token::new(TokenType::String("key".into()), 1, 2))
+        );
+        assert_eq!(
+            lexer.expect(TokenType::Comma),
+            Err(ParseError::UnexpectedToken {
+                found: TokenType::Colon,
+                line: 1,
+                col: 7
+            })
+        );
+        assert_eq!(
+            lexer.expect(TokenType::Integer(String::new())),
+            Ok(Token::new(TokenType::Integer("5".to_string()), 1, 9))
+        );
+        assert_eq!(
+            lexer.expect(TokenType::ObjectEnd),
+            Ok(Token::new(TokenType::ObjectEnd, 1, 10))
+        );
+        assert_eq!(
+            lexer.expect(TokenType::ObjectEnd),
+            Err(ParseError::UnexpectedEof { line: 1, col: 11 })
+        );
+    }
+
+    #[test]
+    fn tab_width() {
+        let json_str = "[\t5]";
+        let mut lexer = Tokenizer::new(json_str.chars());
+        let tokens = lexer.tokenize();
+        assert_eq!(
+            tokens[1],
+            Token::new(TokenType::Integer("5".to_string()), 1, 3)
+        );
+
+        let options = TokenizerOptions {
+            tab_width: 4,
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options(json_str.chars(), options);
+        let tokens = lexer.tokenize();
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Integer("5".to_string()), 1, 6),
+            Token::new(TokenType::ArrayEnd, 1, 7),
+        ];
+        assert_eq!(tokens, expected_tokens);
+    }
+
+    #[test]
+    fn same_type() {
+        let first = Token::new(TokenType::Integer("5".to_string()), 1, 1);
+        let second = Token::new(TokenType::Integer("5".to_string()), 1, 12);
+        assert_ne!(first, second);
+        assert!(first.same_type(&second));
+        assert!(!first.same_type(&Token::new(TokenType::Integer("6".to_string()), 1, 1)));
+        assert!(!first.same_type(&Token::new(TokenType::Comma, 1, 1)));
+    }
+
+    #[test]
+    fn zero_based_positions() {
+        let json_str = "{\"a\":\n\t[1,\n x]}";
+        let options = TokenizerOptions {
+            zero_based_positions: true,
+            ..TokenizerOptions::default()
+        };
+        let positions = |options: TokenizerOptions| {
+            let mut lexer = Tokenizer::with_options(json_str.chars(), options);
+            let tokens = lexer.tokenize();
+            let positions: Vec<(i32, i32)> = tokens
+                .iter()
+                .map(|token| (token.line(), token.position()))
+                .collect();
+            (
+                positions,
+                lexer.error().cloned(),
+                lexer.offset_to_line_col(8),
+            )
+        };
+
+        let (one_based, error, line_col) = positions(TokenizerOptions::default());
+        assert_eq!(one_based, [(1, 1), (1, 2), (1, 5), (2, 2), (2, 3), (2, 4)]);
+        assert_eq!(
+            error,
+            Some(ParseError::UnexpectedChar {
+                ch: 'x',
+                line: 3,
+                col: 2
+            })
+        );
+        assert_eq!(line_col, (2, 3));
+
+        let (zero_based, error, line_col) = positions(options);
+        assert_eq!(zero_based, [(0, 0), (0, 1), (0, 4), (1, 1), (1, 2), (1, 3)]);
+        assert_eq!(
+            error,
+            Some(ParseError::UnexpectedChar {
+                ch: 'x',
+                line: 2,
+                col: 1
+            })
+        );
+        assert_eq!(line_col, (1, 2));
+    }
+
+    #[test]
+    fn offset_to_line_col() {
+        let json_str = "{\n  \"a\": 1,\n  \"é\": [\n\n  ]\n}";
+        let lexer = Tokenizer::new(json_str.chars());
+        assert_eq!(lexer.line_starts(), &[0, 2, 12, 22, 23, 27]);
+        assert_eq!(lexer.offset_to_line_col(0), (1, 1));
+        assert_eq!(lexer.offset_to_line_col(1), (1, 2));
+        assert_eq!(lexer.offset_to_line_col(4), (2, 3));
+        // "é" takes two bytes but a single column.
+        assert_eq!(lexer.offset_to_line_col(19), (3, 7));
+        assert_eq!(lexer.offset_to_line_col(22), (4, 1));
+        assert_eq!(lexer.offset_to_line_col(26), (5, 4));
+        assert_eq!(lexer.offset_to_line_col(1000), (6, 2));
+    }
+
+    #[test]
+    fn minus_sign() {
+        let mut lexer = Tokenizer::new("-5".chars());
+        let tokens = lexer.tokenize();
+        assert_eq!(
+            tokens,
+            vec![Token::new(TokenType::Integer("-5".to_string()), 1, 1)]
+        );
+
+        let mut lexer = Tokenizer::new("- 5".chars());
+        assert_eq!(lexer.tokenize(), vec![]);
+
+        let mut lexer = Tokenizer::new("[-]".chars());
+        assert_eq!(
+            lexer.tokenize(),
+            vec![Token::new(TokenType::ArrayStart, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn exponent_forms() {
+        let exponents = r#"[2.0879878e243, -32.928e+54, 1.5E-5, 1E5, 7e+2, 3e-0, 5e3]"#;
+        let mut lexer = Tokenizer::new(exponents.chars());
+        let numbers: Vec<TokenType> = lexer
+            .tokenize()
+            .into_iter()
+            .map(|token| token.token_type)
+            .filter(|token_type| *token_type != TokenType::Comma)
+            .collect();
+        let expected_numbers = vec![
+            TokenType::ArrayStart,
+            TokenType::Float("2.0879878e243".to_string()),
+            TokenType::Float("-32.928e+54".to_string()),
+            TokenType::Float("1.5E-5".to_string()),
+            TokenType::Float("1E5".to_string()),
+            TokenType::Float("7e+2".to_string()),
+            TokenType::Float("3e-0".to_string()),
+            TokenType::Float("5e3".to_string()),
+            TokenType::ArrayEnd,
+        ];
+        assert_eq!(numbers, expected_numbers);
+
+        for incomplete in ["1e", "1e+", "1.5E-"] {
+            let mut lexer = Tokenizer::new(incomplete.chars());
+            assert_eq!(lexer.tokenize(), vec![]);
+        }
+
+        // Whitespace cannot separate the exponent from its digits.
+        for spaced in ["1e 5", "1e+ 5", "[1E\t5]", "1e\n5"] {
+            let mut lexer = Tokenizer::new(spaced.chars());
+            assert!(
+                matches!(
+                    lexer.try_tokenize(),
+                    Err(ParseError::InvalidNumber { line: 1, .. })
+                ),
+                "{:?}",
+                spaced
+            );
+        }
+        let mut lexer = Tokenizer::new("1e5".chars());
+        assert_eq!(
+            lexer.try_tokenize(),
+            Ok(vec![Token::new(TokenType::Float("1e5".to_string()), 1, 1)])
+        );
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let mut lexer = Tokenizer::new("\u{feff}[]".chars());
+        let tokens = lexer.tokenize();
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 2),
+            Token::new(TokenType::ArrayEnd, 1, 3),
+        ];
+        assert_eq!(tokens, expected_tokens);
+        assert_eq!(lexer.error(), None);
+
+        let mut lexer = Tokenizer::new("[\u{feff}]".chars());
+        assert_eq!(
+            lexer.tokenize(),
+            vec![Token::new(TokenType::ArrayStart, 1, 1)]
+        );
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::UnexpectedChar {
+                ch: '\u{feff}',
+                line: 1,
+                col: 2
+            })
+        );
+    }
+
+    #[test]
+    fn consecutive_escapes() {
+        let json_str = r#"["\u0041\n\u0042", "\u00e9\\\u0043\"", "\t\u0044"]"#;
+        let mut lexer = Tokenizer::new(json_str.chars());
+        let tokens = lexer.tokenize();
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::String(r#"\u0041\n\u0042"#.into()), 1, 2),
+            Token::new(TokenType::Comma, 1, 18),
+            Token::new(TokenType::String(r#"\u00e9\\\u0043\""#.into()), 1, 20),
+            Token::new(TokenType::Comma, 1, 38),
+            Token::new(TokenType::String(r#"\t\u0044"#.into()), 1, 40),
+            Token::new(TokenType::ArrayEnd, 1, 50),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // An invalid escape must not leave the rest of the string mangled.
+        let mut lexer = Tokenizer::new(r#"["\qabc", 1]"#.chars());
+        assert_eq!(
+            lexer.tokenize(),
+            vec![Token::new(TokenType::ArrayStart, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn max_tokens() {
+        let options = TokenizerOptions {
+            max_tokens: Some(4),
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options("[1, 2]".chars(), options.clone());
+        assert_eq!(lexer.tokenize().len(), 4);
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::TooManyTokens {
+                limit: 4,
+                line: 1,
+                col: 6
+            })
+        );
+
+        let mut lexer = Tokenizer::with_options("[1]".chars(), options);
+        assert_eq!(lexer.tokenize().len(), 3);
+        assert_eq!(lexer.error(), None);
+    }
+
+    #[test]
+    fn reject_empty() {
+        let mut lexer = Tokenizer::new("   ".chars());
+        assert_eq!(lexer.tokenize(), vec![]);
+        assert_eq!(lexer.error(), None);
+
+        let options = TokenizerOptions {
+            reject_empty: true,
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options("   ".chars(), options.clone());
+        assert_eq!(lexer.tokenize(), vec![]);
+        assert_eq!(lexer.error(), Some(&ParseError::EmptyDocument));
+
+        let mut lexer = Tokenizer::with_options(" null ".chars(), options);
+        assert_eq!(lexer.tokenize(), vec![Token::new(TokenType::Null, 1, 2)]);
+        assert_eq!(lexer.error(), None);
+    }
+
+    #[test]
+    fn digit_underscores() {
+        let mut lexer = Tokenizer::new("1_000".chars());
+        assert_eq!(lexer.tokenize(), vec![]);
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::InvalidNumber { line: 1, col: 1 })
+        );
+
+        let options = TokenizerOptions {
+            digit_underscores: true,
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options("[1_000, -2_5.0_1e1_0]".chars(), options.clone());
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Integer("1000".to_string()), 1, 2),
+            Token::new(TokenType::Comma, 1, 7),
+            Token::new(TokenType::Float("-25.01e10".to_string()), 1, 9),
+            Token::new(TokenType::ArrayEnd, 1, 21),
+        ];
+        assert_eq!(lexer.tokenize(), expected_tokens);
+
+        for input in ["1_", "1__0", "1_.5", "1._5"] {
+            let mut lexer = Tokenizer::with_options(input.chars(), options.clone());
+            assert_eq!(lexer.tokenize(), vec![], "{}", input);
+            assert_eq!(
+                lexer.error(),
+                Some(&ParseError::InvalidNumber { line: 1, col: 1 }),
+                "{}",
+                input
+            );
+        }
+        // A leading underscore does not start a number at all.
+        let mut lexer = Tokenizer::with_options("_1".chars(), options);
+        assert_eq!(lexer.tokenize(), vec![]);
+    }
+
+    #[test]
+    fn hex_numbers() {
+        let mut lexer = Tokenizer::new("0x1F".chars());
+        assert_eq!(lexer.tokenize(), vec![]);
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::InvalidNumber { line: 1, col: 1 })
+        );
+
+        let options = TokenizerOptions {
+            hex_numbers: true,
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options("[0x1F, 0XFF, -0xa, 0]".chars(), options.clone());
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Integer("31".to_string()), 1, 2),
+            Token::new(TokenType::Comma, 1, 6),
+            Token::new(TokenType::Integer("255".to_string()), 1, 8),
+            Token::new(TokenType::Comma, 1, 12),
+            Token::new(TokenType::Integer("-10".to_string()), 1, 14),
+            Token::new(TokenType::Comma, 1, 18),
+            Token::new(TokenType::Integer("0".to_string()), 1, 20),
+            Token::new(TokenType::ArrayEnd, 1, 21),
+        ];
+        assert_eq!(lexer.tokenize(), expected_tokens);
+
+        for input in ["0x", "-0x", "0x10000000000000000"] {
+            let mut lexer = Tokenizer::with_options(input.chars(), options.clone());
+            assert_eq!(lexer.tokenize(), vec![], "{}", input);
+            assert_eq!(
+                lexer.error(),
+                Some(&ParseError::InvalidNumber { line: 1, col: 1 }),
+                "{}",
+                input
+            );
+        }
+        // Only a single zero starts the prefix, the `x` is not part of the number here.
+        let mut lexer = Tokenizer::with_options("00x1".chars(), options);
+        assert_eq!(
+            lexer.try_tokenize(),
+            Err(ParseError::UnexpectedChar {
+                ch: 'x',
+                line: 1,
+                col: 3
+            })
+        );
+    }
+
+    #[test]
+    fn long_numbers() {
+        // Numbers and strings are read in a single pass, so even huge ones are quick.
+        let digits = "1234567890".repeat(10_000);
+        let json_str = format!("[{0}, -{0}.{0}e{0}, \"{0}\"]", digits);
+        let tokens = Tokenizer::new(json_str.chars()).try_tokenize().unwrap();
+        assert_eq!(tokens.len(), 7);
+        assert_eq!(tokens[1].token_type(), &TokenType::Integer(digits.clone()));
+        assert_eq!(
+            tokens[3].token_type(),
+            &TokenType::Float(format!("-{0}.{0}e{0}", digits))
+        );
+        assert_eq!(tokens[5].position(), json_str.find('"').unwrap() as i32 + 1);
+    }
+
+    #[test]
+    fn max_number_digits() {
+        let options = TokenizerOptions {
+            max_number_digits: Some(5),
+            ..TokenizerOptions::default()
+        };
+        let mut lexer = Tokenizer::with_options("[12345, -1.2e3]".chars(), options.clone());
+        assert_eq!(lexer.tokenize().len(), 5);
+        assert_eq!(lexer.error(), None);
+
+        let mut lexer = Tokenizer::with_options("[1, 123456]".chars(), options.clone());
+        assert_eq!(lexer.tokenize().len(), 3);
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::NumberTooLong {
+                limit: 5,
+                line: 1,
+                col: 5
+            })
+        );
+
+        let mut lexer = Tokenizer::with_options("[1,\n  123456]".chars(), options.clone());
+        lexer.tokenize();
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::NumberTooLong {
+                limit: 5,
+                line: 2,
+                col: 3
+            })
+        );
+
+        // Digits of the fraction and exponent count as well.
+        let mut lexer = Tokenizer::with_options("12.3e456".chars(), options);
+        assert_eq!(lexer.tokenize(), vec![]);
+        assert!(lexer.error().is_some());
+    }
+
+    #[test]
+    fn spans() {
+        let json_str = "{\"é\": [1.5, null]}\n";
+        let mut lexer = Tokenizer::new(json_str.chars());
+        let spans: Vec<_> = lexer
+            .tokenize_with_spans()
+            .into_iter()
+            .map(|(_, span)| span)
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                0..1,
+                1..5,
+                5..6,
+                7..8,
+                8..11,
+                11..12,
+                13..17,
+                17..18,
+                18..19
+            ]
+        );
+        assert_eq!(&json_str[8..11], "1.5");
+
+        let mut lexer = Tokenizer::new("\u{feff} true".chars());
+        let tokens = lexer.tokenize_with_spans();
+        assert_eq!(
+            tokens,
+            vec![(Token::new(TokenType::Boolean(true), 1, 3), 4..8)]
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let json_str = "{\n  \"name\": \"hdjson\",\n  \"tags\": [\n    1,\n    true\n  ]\n}\n";
+        let mut lexer = Tokenizer::new(json_str.chars());
+        let tokens = lexer.tokenize();
+        let expected_tokens = vec![
+            Token::new(TokenType::ObjectStart, 1, 1),
+            Token::new(TokenType::String("name".into()), 2, 3),
+            Token::new(TokenType::Colon, 2, 9),
+            Token::new(TokenType::String("hdjson".into()), 2, 11),
+            Token::new(TokenType::Comma, 2, 19),
+            Token::new(TokenType::String("tags".into()), 3, 3),
+            Token::new(TokenType::Colon, 3, 9),
+            Token::new(TokenType::ArrayStart, 3, 11),
+            Token::new(TokenType::Integer("1".to_string()), 4, 5),
+            Token::new(TokenType::Comma, 4, 6),
+            Token::new(TokenType::Boolean(true), 5, 5),
+            Token::new(TokenType::ArrayEnd, 6, 3),
+            Token::new(TokenType::ObjectEnd, 7, 1),
+        ];
+        assert_eq!(tokens, expected_tokens);
+        assert_eq!(tokens[10].line(), 5);
+
+        // Positions agree with the offset lookup.
+        let offset = json_str.find("true").unwrap();
+        assert_eq!(lexer.offset_to_line_col(offset), (5, 5));
+    }
+
+    #[test]
+    fn starting_at() {
+        let fragment = "{\"a\": 1,\n \"b\": ?}";
+        let mut lexer = Tokenizer::new(fragment.chars()).starting_at(10, 5);
+        let expected_tokens = vec![
+            Token::new(TokenType::ObjectStart, 10, 5),
+            Token::new(TokenType::String("a".into()), 10, 6),
+            Token::new(TokenType::Colon, 10, 9),
+            Token::new(TokenType::Integer("1".to_string()), 10, 11),
+            Token::new(TokenType::Comma, 10, 12),
+            Token::new(TokenType::String("b".into()), 11, 2),
+            Token::new(TokenType::Colon, 11, 5),
+        ];
+        assert_eq!(lexer.tokenize(), expected_tokens);
+        assert_eq!(
+            lexer.error(),
+            Some(&ParseError::UnexpectedChar {
+                ch: '?',
+                line: 11,
+                col: 7
+            })
+        );
+        assert_eq!(lexer.offset_to_line_col(1), (10, 6));
+        assert_eq!(lexer.offset_to_line_col(10), (11, 2));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |input: &str| {
+            let mut lexer = Tokenizer::new(input.chars());
+            lexer.tokenize();
+            lexer.error().cloned()
+        };
+        assert_eq!(
+            error(r#""abc"#),
+            Some(ParseError::UnterminatedString { line: 1, col: 1 })
+        );
+        assert_eq!(
+            error(r#"[1, "abc\u00"#),
+            Some(ParseError::UnterminatedString { line: 1, col: 5 })
+        );
+        assert_eq!(
+            error(r#"["a\x"]"#),
+            Some(ParseError::InvalidEscape {
+                found: r"\x".to_string(),
+                line: 1,
+                col: 4
+            })
+        );
+        assert_eq!(
+            error(r#""\u12G4""#),
+            Some(ParseError::InvalidEscape {
+                found: r"\u12G".to_string(),
+                line: 1,
+                col: 2
+            })
+        );
+        let mut lexer = Tokenizer::new(r#""ab\q""#.chars());
+        let escape_error = lexer.try_tokenize().unwrap_err();
+        assert_eq!(
+            escape_error,
+            ParseError::InvalidEscape {
+                found: r"\q".to_string(),
+                line: 1,
+                col: 4
+            }
+        );
+        assert_eq!(
+            escape_error.to_string(),
+            r"invalid escape sequence \q at line 1, column 4"
+        );
+        assert_eq!(
+            error("\n \"a\tb\""),
+            Some(ParseError::ControlCharInString {
+                ch: '\t',
+                line: 2,
+                col: 4
+            })
+        );
+        assert_eq!(
+            error("[- 1]"),
+            Some(ParseError::InvalidNumber { line: 1, col: 2 })
+        );
+        assert_eq!(
+            error("1e+"),
+            Some(ParseError::InvalidNumber { line: 1, col: 1 })
+        );
+        assert_eq!(error("[1]"), None);
+    }
+
+    #[test]
+    fn iterator() {
+        let json_str = r#"{"a": [1, true, null], "b": "c"}"#;
+        let expected_tokens = Tokenizer::new(json_str.chars()).tokenize();
+        let mut tokens = vec![];
+        for token in Tokenizer::new(json_str.chars()) {
+            tokens.push(token);
+        }
+        assert_eq!(tokens, expected_tokens);
+
+        let strings: Vec<TokenType> = Tokenizer::new(json_str.chars())
+            .map(|token| token.token_type)
+            .filter(|token_type| matches!(token_type, TokenType::String(_)))
+            .collect();
+        assert_eq!(strings.len(), 3);
+
+        let mut lexer = Tokenizer::new("[1, ?]".chars());
+        assert_eq!(lexer.by_ref().count(), 3);
+        assert!(lexer.error().is_some());
+    }
+
+    #[test]
+    fn peek_token() {
+        let json_str = "{\n  \"a\": 1\n}";
+        let expected_tokens = Tokenizer::new(json_str.chars()).tokenize();
+
+        let mut lexer = Tokenizer::new(json_str.chars());
+        let mut tokens = vec![];
+        loop {
+            let peeked = lexer.peek_token().cloned();
+            assert_eq!(lexer.peek_token().cloned(), peeked);
+            let token = lexer.next_token();
+            assert_eq!(token, peeked);
+            match token {
+                Some(token) => tokens.push(token),
+                None => break,
+            }
+        }
         assert_eq!(tokens, expected_tokens);
+        assert_eq!(
+            tokens[3],
+            Token::new(TokenType::Integer("1".to_string()), 2, 8)
+        );
+
+        // The position is only advanced by the first peek.
+        let mut lexer = Tokenizer::new("[12, 3]".chars());
+        lexer.next_token();
+        lexer.peek_token();
+        let column = lexer.column();
+        lexer.peek_token();
+        assert_eq!(lexer.column(), column);
+        lexer.next_token();
+        assert_eq!(lexer.column(), column);
+
+        // A malformed token is not read again after peeking.
+        let mut lexer = Tokenizer::new("[?1]".chars());
+        lexer.next_token();
+        assert_eq!(lexer.peek_token(), None);
+        assert_eq!(lexer.next_token(), None);
+        assert!(lexer.error().is_some());
+    }
+
+    #[test]
+    fn ascii_fast_path() {
+        // The second input holds a non-ASCII character in its last string, so it is tokenized
+        // without the fast path. Both must agree on everything before it.
+        let json_str = "[{\"key\": \"plain text\", \"esc\\\"aped\": \"a\\nb\"},\n \"more\", ";
+        let ascii = format!("{}\"e\"]", json_str);
+        let non_ascii = format!("{}\"é\"]", json_str);
+        let ascii_tokens = Tokenizer::new(ascii.chars()).tokenize();
+        let mut non_ascii_tokens = Tokenizer::new(non_ascii.chars()).tokenize();
+        assert_eq!(ascii_tokens.len(), non_ascii_tokens.len());
+        assert_eq!(
+            non_ascii_tokens[13],
+            Token::new(TokenType::String("é".into()), 2, 10)
+        );
+        non_ascii_tokens[13] = Token::new(TokenType::String("e".into()), 2, 10);
+        assert_eq!(ascii_tokens, non_ascii_tokens);
+        assert_eq!(
+            ascii_tokens[4],
+            Token::new(TokenType::String("plain text".into()), 1, 10)
+        );
+
+        let mut lexer = Tokenizer::new("\"abc".chars());
+        assert_eq!(
+            lexer.try_tokenize(),
+            Err(ParseError::UnterminatedString { line: 1, col: 1 })
+        );
+    }
+
+    #[test]
+    fn try_tokenize() {
+        let mut lexer = Tokenizer::new("[true]".chars());
+        let expected_tokens = vec![
+            Token::new(TokenType::ArrayStart, 1, 1),
+            Token::new(TokenType::Boolean(true), 1, 2),
+            Token::new(TokenType::ArrayEnd, 1, 6),
+        ];
+        assert_eq!(lexer.try_tokenize(), Ok(expected_tokens));
+
+        let mut lexer = Tokenizer::new("[true, @]".chars());
+        assert_eq!(
+            lexer.try_tokenize(),
+            Err(ParseError::UnexpectedChar {
+                ch: '@',
+                line: 1,
+                col: 8
+            })
+        );
     }
 }
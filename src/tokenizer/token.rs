@@ -1,5 +1,9 @@
-#[derive(Debug, PartialEq)]
-pub enum TokenType {
+use crate::parser::decode_string;
+use std::borrow::Cow;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenType<'a> {
     ObjectStart,
     ObjectEnd,
     ArrayStart,
@@ -8,20 +12,110 @@ pub enum TokenType {
     Colon,
     Integer(String),
     Float(String),
-    String(String),
+    // Escape sequences are kept as written, so the contents can be borrowed from the input.
+    String(Cow<'a, str>),
+    Boolean(bool),
+    Null,
+    // Only produced in JSONC mode. Holds the comment including its delimiters.
+    Comment(String),
+}
+
+impl TokenType<'_> {
+    /// Copies borrowed contents, so the token type can outlive the input.
+    pub fn into_owned(self) -> TokenType<'static> {
+        match self {
+            TokenType::ObjectStart => TokenType::ObjectStart,
+            TokenType::ObjectEnd => TokenType::ObjectEnd,
+            TokenType::ArrayStart => TokenType::ArrayStart,
+            TokenType::ArrayEnd => TokenType::ArrayEnd,
+            TokenType::Comma => TokenType::Comma,
+            TokenType::Colon => TokenType::Colon,
+            TokenType::Integer(number) => TokenType::Integer(number),
+            TokenType::Float(number) => TokenType::Float(number),
+            TokenType::String(string) => TokenType::String(Cow::Owned(string.into_owned())),
+            TokenType::Boolean(boolean) => TokenType::Boolean(boolean),
+            TokenType::Null => TokenType::Null,
+            TokenType::Comment(comment) => TokenType::Comment(comment),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Token {
-    token_type: TokenType,
-    position: i32,
+impl fmt::Display for TokenType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenType::ObjectStart => write!(f, "'{{'"),
+            TokenType::ObjectEnd => write!(f, "'}}'"),
+            TokenType::ArrayStart => write!(f, "'['"),
+            TokenType::ArrayEnd => write!(f, "']'"),
+            TokenType::Comma => write!(f, "','"),
+            TokenType::Colon => write!(f, "':'"),
+            TokenType::Integer(number) | TokenType::Float(number) => {
+                write!(f, "number {}", number)
+            }
+            TokenType::String(string) => write!(f, "string \"{}\"", string),
+            TokenType::Boolean(boolean) => write!(f, "{}", boolean),
+            TokenType::Null => write!(f, "null"),
+            TokenType::Comment(_) => write!(f, "comment"),
+        }
+    }
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, position: i32) -> Token {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub(crate) token_type: TokenType<'a>,
+    // Line and column the token starts at, see `TokenizerOptions::zero_based_positions`.
+    pub(crate) line: i32,
+    pub(crate) position: i32,
+}
+
+impl<'a> Token<'a> {
+    pub fn new(token_type: TokenType<'a>, line: i32, position: i32) -> Token<'a> {
         Token {
             token_type,
+            line,
             position,
         }
     }
+
+    /// What kind of token this is, together with its text for numbers, strings and comments.
+    ///
+    /// ```
+    /// use hdjson::tokenizer::{TokenType, Tokenizer};
+    ///
+    /// let tokens = Tokenizer::new("[\"a\", 1]".chars()).tokenize();
+    /// let first = &tokens[0];
+    /// assert_eq!(first.token_type(), &TokenType::ArrayStart);
+    /// assert_eq!((first.line(), first.position()), (1, 1));
+    /// assert_eq!(tokens[1].token_type(), &TokenType::String("a".into()));
+    /// ```
+    pub fn token_type(&self) -> &TokenType<'a> {
+        &self.token_type
+    }
+
+    /// The column the token starts at, the first column being 1, or 0 with
+    /// `TokenizerOptions::zero_based_positions`.
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// The line the token starts on, the first line being 1, or 0 with
+    /// `TokenizerOptions::zero_based_positions`.
+    pub fn line(&self) -> i32 {
+        self.line
+    }
+
+    /// The contents of a string token with its escape sequences translated, `\u0041`
+    /// becoming `A`. `token_type` keeps the string exactly as written. Returns `None` for other
+    /// tokens. Strings without escapes are returned as they are, only decoding allocates.
+    pub fn decoded_value(&self) -> Option<Cow<'_, str>> {
+        match &self.token_type {
+            TokenType::String(raw) => decode_string(raw),
+            _ => None,
+        }
+    }
+
+    /// Compares two tokens by their type and contents, ignoring where they were found.
+    pub fn same_type(&self, other: &Token) -> bool {
+        self.token_type == other.token_type
+    }
 }
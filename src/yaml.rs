@@ -0,0 +1,422 @@
+//! Conversion between JSON values and a subset of YAML, enabled by the `yaml` feature.
+//!
+//! Supported are block mappings (`key: value`), block sequences (`- value`), comments, and the
+//! scalars `null`/`~`, `true`/`false`, JSON numbers and plain, single- or double-quoted strings.
+//! Flow collections such as `[1, 2]` or `{"a": 1}` are accepted as long as they are valid JSON.
+//! Anchors, tags, multi-line scalars and multiple documents are not supported.
+
+use crate::json_value::JsonValue;
+use crate::parser::Parser;
+use crate::tokenizer::Tokenizer;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlError {
+    pub line: i32,
+    pub message: String,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}", self.message, self.line)
+    }
+}
+
+impl Error for YamlError {}
+
+impl JsonValue {
+    /// Serializes the value as block-style YAML. Strings are always double-quoted, so they can
+    /// never be mistaken for numbers, booleans or `null` when read back.
+    pub fn to_yaml(&self) -> String {
+        let mut output = String::new();
+        match self {
+            JsonValue::Array(elements) if !elements.is_empty() => {
+                write_block(&mut output, self, 0, false)
+            }
+            JsonValue::Object(members) if !members.is_empty() => {
+                write_block(&mut output, self, 0, false)
+            }
+            _ => {
+                write_scalar(&mut output, self);
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    /// Parses a YAML document in the subset described in the module documentation. Plain
+    /// scalars that are valid JSON numbers, such as `1`, `1.5`, `1e3` or `1e400`, become numbers,
+    /// `null` and `~` become null, and everything else that is not quoted is read as a string.
+    pub fn from_yaml(input: &str) -> Result<JsonValue, YamlError> {
+        let mut lines = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let content = strip_comment(line).trim_end();
+            let trimmed = content.trim_start();
+            if trimmed.is_empty() || (trimmed == "---" && lines.is_empty()) {
+                continue;
+            }
+            if content.starts_with('\t') {
+                return Err(error(i + 1, "tabs are not allowed for indentation"));
+            }
+            lines.push(Line {
+                number: i + 1,
+                indent: content.len() - trimmed.len(),
+                content: trimmed,
+            });
+        }
+
+        if lines.is_empty() {
+            return Ok(JsonValue::Null);
+        }
+        let mut parser = YamlParser { lines, pos: 0 };
+        let value = parser.parse_node(0)?;
+        match parser.lines.get(parser.pos) {
+            Some(line) => Err(error(line.number, "unexpected indentation")),
+            None => Ok(value),
+        }
+    }
+}
+
+fn write_scalar(output: &mut String, value: &JsonValue) {
+    match value {
+        JsonValue::Array(_) => output.push_str("[]"),
+        JsonValue::Object(_) => output.push_str("{}"),
+        // JSON strings and numbers are valid YAML scalars as they are.
+        _ => output.push_str(&value.to_string()),
+    }
+}
+
+// Writes a non-empty container, one entry per line. With `inline` the first entry continues the
+// current line, as in `- a: 1`.
+fn write_block(output: &mut String, value: &JsonValue, indent: usize, inline: bool) {
+    let pad = |output: &mut String, i: usize| {
+        if i > 0 || !inline {
+            output.push_str(&" ".repeat(indent));
+        }
+    };
+
+    match value {
+        JsonValue::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                pad(output, i);
+                output.push('-');
+                write_entry(output, element, indent, true);
+            }
+        }
+        JsonValue::Object(members) => {
+            for (i, (key, member)) in members.iter().enumerate() {
+                pad(output, i);
+                write_key(output, key);
+                output.push(':');
+                write_entry(output, member, indent, false);
+            }
+        }
+        _ => unreachable!("only containers are written as blocks"),
+    }
+}
+
+// Writes what follows a `-` or `key:`.
+fn write_entry(output: &mut String, value: &JsonValue, indent: usize, in_sequence: bool) {
+    let is_block = match value {
+        JsonValue::Array(elements) => !elements.is_empty(),
+        JsonValue::Object(members) => !members.is_empty(),
+        _ => false,
+    };
+
+    if !is_block {
+        output.push(' ');
+        write_scalar(output, value);
+        output.push('\n');
+    } else if in_sequence {
+        output.push(' ');
+        write_block(output, value, indent + 2, true);
+    } else {
+        output.push('\n');
+        write_block(output, value, indent + 2, false);
+    }
+}
+
+fn write_key(output: &mut String, key: &str) {
+    let is_plain = !key.is_empty()
+        && !key.starts_with(['-', '.'])
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' || ch == '.')
+        && parse_plain(key) == JsonValue::String(key.to_string());
+    if is_plain {
+        output.push_str(key);
+    } else {
+        output.push_str(&JsonValue::String(key.to_string()).to_string());
+    }
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    content: &'a str,
+}
+
+struct YamlParser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+impl YamlParser<'_> {
+    fn parse_node(&mut self, indent: usize) -> Result<JsonValue, YamlError> {
+        let line = &self.lines[self.pos];
+        if line.indent != indent {
+            return Err(error(line.number, "unexpected indentation"));
+        }
+
+        if line.content == "-" || line.content.starts_with("- ") {
+            self.parse_sequence(indent)
+        } else if split_key(line.content).is_some() {
+            self.parse_mapping(indent)
+        } else {
+            let (number, content) = (line.number, line.content);
+            self.pos += 1;
+            parse_inline(content, number)
+        }
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<JsonValue, YamlError> {
+        let mut elements = vec![];
+        while let Some(line) = self.lines.get_mut(self.pos) {
+            if line.indent != indent || !(line.content == "-" || line.content.starts_with("- ")) {
+                break;
+            }
+
+            let rest = line.content[1..].trim_start();
+            if rest.is_empty() {
+                self.pos += 1;
+                elements.push(self.parse_child(indent)?);
+            } else {
+                // Whatever follows the dash is parsed as if it started its own, deeper line.
+                line.indent += line.content.len() - rest.len();
+                line.content = rest;
+                let child_indent = line.indent;
+                elements.push(self.parse_node(child_indent)?);
+            }
+        }
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<JsonValue, YamlError> {
+        let mut members = vec![];
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.indent != indent {
+                break;
+            }
+            let Some((key, rest)) = split_key(line.content) else {
+                return Err(error(line.number, "expected a mapping key"));
+            };
+
+            let number = line.number;
+            let key = match parse_inline(key, number)? {
+                JsonValue::String(key) => key,
+                _ => key.to_string(),
+            };
+            self.pos += 1;
+            let member = if !rest.is_empty() {
+                parse_inline(rest, number)?
+            } else if matches!(self.lines.get(self.pos), Some(next)
+                if next.indent == indent && next.content.starts_with('-'))
+            {
+                // Sequences are commonly not indented below their key.
+                self.parse_sequence(indent)?
+            } else {
+                self.parse_child(indent)?
+            };
+            members.push((key, member));
+        }
+        Ok(JsonValue::Object(members.into_iter().collect()))
+    }
+
+    // Parses the value on the lines below an entry without an inline value, or null if there is
+    // no deeper indented line.
+    fn parse_child(&mut self, indent: usize) -> Result<JsonValue, YamlError> {
+        match self.lines.get(self.pos) {
+            Some(next) if next.indent > indent => {
+                let child_indent = next.indent;
+                self.parse_node(child_indent)
+            }
+            _ => Ok(JsonValue::Null),
+        }
+    }
+}
+
+// Splits `key: value` on the first colon that is followed by a space or the end of the line and
+// is not inside quotes.
+fn split_key(content: &str) -> Option<(&str, &str)> {
+    if content.starts_with(['[', '{']) {
+        return None;
+    }
+
+    let mut quote = None;
+    for (i, ch) in content.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') if i == 0 => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, ':') => {
+                let rest = &content[i + 1..];
+                if rest.is_empty() || rest.starts_with(' ') {
+                    return Some((content[..i].trim_end(), rest.trim_start()));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, ch) in line.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, '#') if previous == ' ' => return &line[..i],
+            _ => {}
+        }
+        previous = ch;
+    }
+    line
+}
+
+fn parse_inline(content: &str, line: usize) -> Result<JsonValue, YamlError> {
+    if content.starts_with(['"', '[', '{']) {
+        // Double-quoted scalars and flow collections are read as JSON.
+        return Parser::new(Tokenizer::new(content.chars()))
+            .parse()
+            .map_err(|e| error(line, &e.to_string()));
+    }
+    if let Some(quoted) = content.strip_prefix('\'') {
+        return match quoted.strip_suffix('\'') {
+            Some(quoted) => Ok(JsonValue::String(quoted.replace("''", "'"))),
+            None => Err(error(line, "unterminated single-quoted string")),
+        };
+    }
+    Ok(parse_plain(content))
+}
+
+fn parse_plain(content: &str) -> JsonValue {
+    match content {
+        "null" | "Null" | "NULL" | "~" => JsonValue::Null,
+        "true" | "True" | "TRUE" => JsonValue::Boolean(true),
+        "false" | "False" | "FALSE" => JsonValue::Boolean(false),
+        // Numbers are read as JSON, anything else that starts like one is a string.
+        _ if content.starts_with(|ch: char| ch == '-' || ch.is_ascii_digit()) => {
+            match Parser::new(Tokenizer::new(content.chars())).parse() {
+                Ok(number @ JsonValue::Number(_)) => number,
+                _ => JsonValue::String(content.to_string()),
+            }
+        }
+        _ => JsonValue::String(content.to_string()),
+    }
+}
+
+fn error(line: usize, message: &str) -> YamlError {
+    YamlError {
+        line: line as i32,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_value::{JsonValue, Object};
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+    use crate::yaml::YamlError;
+
+    #[test]
+    fn round_trip() {
+        let json_str = r#"{"name":"hdjson","version":1,"ratio":1.5,"delta":-0.25,"huge":1e400,"tags":["json","yaml"],"authors":[{"name":"a","active":true},{"name":"b","active":false}],"matrix":[[1,2],[]],"empty":{},"missing":null,"tricky key":"42"}"#;
+        let value = Parser::new(Tokenizer::new(json_str.chars()))
+            .parse()
+            .unwrap();
+        let yaml = value.to_yaml();
+        assert_eq!(
+            yaml,
+            r#"name: "hdjson"
+version: 1
+ratio: 1.5
+delta: -0.25
+huge: 1e400
+tags:
+  - "json"
+  - "yaml"
+authors:
+  - name: "a"
+    active: true
+  - name: "b"
+    active: false
+matrix:
+  - - 1
+    - 2
+  - []
+empty: {}
+missing: null
+"tricky key": "42"
+"#
+        );
+        assert_eq!(JsonValue::from_yaml(&yaml), Ok(value));
+    }
+
+    #[test]
+    fn from_yaml() {
+        let yaml = r#"
+# A hand-written document
+server:
+  host: localhost   # plain strings
+  port: 8080
+  debug: ~
+paths:
+- /static
+- 'it''s'
+-
+  nested: [1, 2]
+"#;
+        assert_eq!(
+            JsonValue::from_yaml(yaml),
+            Ok(JsonValue::Object(Object::from_iter([
+                (
+                    "server".to_string(),
+                    JsonValue::Object(Object::from_iter([
+                        (
+                            "host".to_string(),
+                            JsonValue::String("localhost".to_string())
+                        ),
+                        ("port".to_string(), JsonValue::Number(8080.into())),
+                        ("debug".to_string(), JsonValue::Null),
+                    ]))
+                ),
+                (
+                    "paths".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::String("/static".to_string()),
+                        JsonValue::String("it's".to_string()),
+                        JsonValue::Object(Object::from_iter([(
+                            "nested".to_string(),
+                            JsonValue::Array(vec![
+                                JsonValue::Number(1.into()),
+                                JsonValue::Number(2.into())
+                            ])
+                        )])),
+                    ])
+                ),
+            ])))
+        );
+
+        assert_eq!(JsonValue::from_yaml("42"), Ok(JsonValue::Number(42.into())));
+        assert_eq!(
+            JsonValue::from_yaml("a: 1\n   b: 2"),
+            Err(YamlError {
+                line: 2,
+                message: "unexpected indentation".to_string()
+            })
+        );
+    }
+}
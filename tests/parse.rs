@@ -0,0 +1,64 @@
+use hdjson::{parse, JsonValue, Object, ParseError};
+
+#[test]
+fn documents() {
+    let json_str = r#"
+        {
+            "name": "hdjson",
+            "version": 1,
+            "keywords": ["json", "parser"],
+            "license": null,
+            "published": false
+        }
+    "#;
+    let value = parse(json_str).unwrap();
+    assert_eq!(
+        value.get_typed::<String>("name"),
+        Some("hdjson".to_string())
+    );
+    assert_eq!(value.get_typed::<i64>("version"), Some(1));
+    assert_eq!(
+        value.get_typed::<Vec<String>>("keywords"),
+        Some(vec!["json".to_string(), "parser".to_string()])
+    );
+    assert_eq!(
+        value.to_string(),
+        r#"{"name":"hdjson","version":1,"keywords":["json","parser"],"license":null,"published":false}"#
+    );
+
+    assert_eq!(parse("42"), Ok(JsonValue::Number(42.into())));
+    assert_eq!(parse(r#""é""#), Ok(JsonValue::String("é".to_string())));
+    assert_eq!(
+        parse("[[], {}]"),
+        Ok(JsonValue::Array(vec![
+            JsonValue::Array(vec![]),
+            JsonValue::Object(Object::new())
+        ]))
+    );
+}
+
+#[test]
+fn malformed_documents() {
+    assert_eq!(
+        parse("[1, 2"),
+        Err(ParseError::UnexpectedEof { line: 1, col: 6 })
+    );
+
+    let error = parse("{\n  \"a\": [1, ?]\n}").unwrap_err();
+    assert_eq!(error.path(), Some("/a/1"));
+    assert_eq!(
+        error.root_cause(),
+        &ParseError::UnexpectedChar {
+            ch: '?',
+            line: 2,
+            col: 12
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "unexpected character '?' at line 2, column 12 in /a/1"
+    );
+
+    assert!(parse("").is_err());
+    assert!(parse(r#"{"a" 1}"#).is_err());
+}